@@ -1,52 +1,34 @@
 use std::env;
 use std::time::Instant;
-use xdevs::devstone::*;
+use xdevs::devstone;
+use xdevs::modeling::{Coupled, ModelRegistry};
 use xdevs::simulation::*;
 
 /// USAGE:
-/// `cargo run <MODEL_TYPE> <WIDTH> <DEPTH>`
-/// - `<MODEL_TYPE>` must be `LI`, `HI`, `HO`, or `HOmod`.
-/// - `WIDTH` must be equal to or greater than 1.
-/// - `DEPTH` must be equal to or greater than 1.
+/// `cargo run --example devstone --features serde -- <DESCRIPTION>`
+/// - `<DESCRIPTION>` is the description of the model (or the path to a file containing it).
+///   It must be in JSON (or TOML, with the `toml` feature) format (see [`ModelRegistry`]).
+/// - Components can be of type `LI`, `HI`, `HO`, or `HOmod`,
+///   with parameters `width`, `depth`, `int_delay`, and `ext_delay` (see [`devstone::DEVStoneParams`]).
+///
+/// For example:
+/// `cargo run --example devstone --features serde -- '{"bench": {"components": {"li": {"type": "LI", "params": {"width": 100, "depth": 10}}}}}'`
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let model_type = args
+    let arg = args
         .get(1)
-        .expect("first argument must select the model type")
-        .clone()
-        .to_lowercase();
-    let width = args
-        .get(2)
-        .expect("second argument must select the width")
-        .parse()
-        .expect("width could not be parsed");
-    let depth = args
-        .get(3)
-        .expect("third argument must select the depth")
-        .parse()
-        .expect("depth could not be parsed");
-
-    let int_delay = match args.get(4) {
-        Some(arg) => arg.parse().expect("int delay could not be parsed"),
-        None => 0,
-    };
-    let ext_delay = match args.get(5) {
-        Some(arg) => arg.parse().expect("ext delay could not be parsed"),
-        None => int_delay,
-    };
+        .expect("first argument must be the description of the model");
+    let description = std::fs::read_to_string(arg).unwrap_or_else(|_| arg.clone());
 
     let start = Instant::now();
-    let coupled = match model_type.as_str() {
-        "li" => LI::create(width, depth, int_delay, ext_delay),
-        "hi" => HI::create(width, depth, int_delay, ext_delay),
-        "ho" => HO::create(width, depth, int_delay, ext_delay),
-        "homod" => HOmod::create(width, depth, int_delay, ext_delay),
-        _ => panic!("unknown DEVStone model type"),
-    };
+    let mut registry = ModelRegistry::new();
+    devstone::register(&mut registry);
+    let coupled = build(&registry, &description);
     let duration = start.elapsed();
     println!("Model creation time: {duration:?}");
     let start = Instant::now();
-    let mut simulator = RootCoordinator::new(coupled);
+    // DEVStone models leave some ports unconnected on purpose
+    let mut simulator = RootCoordinator::unvalidated(coupled);
     let duration = start.elapsed();
     println!("Simulator creation time: {duration:?}");
     let start = Instant::now();
@@ -54,3 +36,16 @@ fn main() {
     let duration = start.elapsed();
     println!("Simulation time: {duration:?}");
 }
+
+/// Builds the model from its JSON or TOML description.
+fn build(registry: &ModelRegistry, description: &str) -> Coupled {
+    if let Ok(json) = serde_json::from_str(description) {
+        return registry.from_json(&json).unwrap_or_else(|e| panic!("{e}"));
+    }
+    #[cfg(feature = "toml")]
+    return registry
+        .from_toml(description)
+        .unwrap_or_else(|e| panic!("{e}"));
+    #[cfg(not(feature = "toml"))]
+    panic!("description could not be parsed as JSON");
+}
@@ -4,48 +4,33 @@ use std::env;
 use xdevs::modeling::*;
 use xdevs::simulation::*;
 
+#[derive(Atomic)]
 struct Generator {
     component: Component,
     sigma: f64,
     period: f64,
     count: usize,
+    #[in_port]
     input: InPort<bool>,
+    #[out_port]
     output: OutPort<usize>,
 }
 impl Generator {
     fn new(name: &str, period: f64) -> Self {
-        let mut component = Component::new(name);
-        let input = component.add_in_port::<bool>("input");
-        let output = component.add_out_port::<usize>("output");
-        Self {
-            sigma: 0.,
-            period,
-            count: 0,
-            input,
-            output,
-            component,
-        }
+        Self::new_with_ports(name, 0., period, 0)
     }
 }
 impl Atomic for Generator {
-    fn get_component(&self) -> &Component {
-        &self.component
-    }
-    fn get_component_mut(&mut self) -> &mut Component {
-        &mut self.component
-    }
-    fn lambda(&self) {
-        // Safety: adding message on atomic model's output port at lambda
-        unsafe { self.output.add_value(self.count) };
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, self.count);
     }
     fn delta_int(&mut self) {
         self.count += 1;
         self.sigma = self.period;
     }
-    fn delta_ext(&mut self, e: f64) {
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
         self.sigma -= e;
-        // Safety: reading messages on atomic model's input port at delta_ext
-        if !unsafe { self.input.is_empty() } {
+        if !input.is_empty(&self.input) {
             self.sigma = f64::INFINITY;
         }
     }
@@ -54,42 +39,26 @@ impl Atomic for Generator {
     }
 }
 
+#[derive(Atomic)]
 struct Processor {
     component: Component,
     sigma: f64,
     time: f64,
     job: Option<usize>,
+    #[in_port]
     input: InPort<usize>,
+    #[out_port]
     output: OutPort<(usize, f64)>,
 }
 impl Processor {
     fn new(name: &str, time: f64) -> Self {
-        let mut component = Component::new(name);
-        let input = component.add_in_port::<usize>("input");
-        let output = component.add_out_port::<(usize, f64)>("output");
-        Self {
-            sigma: f64::INFINITY,
-            time,
-            job: None,
-            input,
-            output,
-            component,
-        }
+        Self::new_with_ports(name, f64::INFINITY, time, None)
     }
 }
 impl Atomic for Processor {
-    fn get_component(&self) -> &Component {
-        &self.component
-    }
-
-    fn get_component_mut(&mut self) -> &mut Component {
-        &mut self.component
-    }
-
-    fn lambda(&self) {
+    fn lambda(&self, output: &OutputCtx) {
         if let Some(job) = self.job {
-            // Safety: adding message on atomic model's output port at lambda
-            unsafe { self.output.add_value((job, self.time)) };
+            output.add_value(&self.output, (job, self.time));
         }
     }
 
@@ -98,11 +67,10 @@ impl Atomic for Processor {
         self.job = None;
     }
 
-    fn delta_ext(&mut self, e: f64) {
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
         self.sigma -= e;
         if self.job.is_none() {
-            // Safety: reading messages on atomic model's input port at delta_ext
-            self.job = Some(*unsafe { self.input.get_values() }.first().unwrap());
+            self.job = Some(*input.get_values(&self.input).first().unwrap());
             self.sigma = self.time;
         }
     }
@@ -112,41 +80,26 @@ impl Atomic for Processor {
     }
 }
 
+#[derive(Atomic)]
 struct Transducer {
     component: Component,
     sigma: f64,
+    #[in_port]
     input_g: InPort<usize>,
+    #[in_port]
     input_p: InPort<(usize, f64)>,
+    #[out_port]
     output: OutPort<bool>,
 }
 
 impl Transducer {
     fn new(name: &str, time: f64) -> Self {
-        let mut component = Component::new(name);
-        let input_g = component.add_in_port::<usize>("input_g");
-        let input_p = component.add_in_port::<(usize, f64)>("input_p");
-        let output = component.add_out_port::<bool>("output");
-        Self {
-            sigma: time,
-            input_g,
-            input_p,
-            output,
-            component,
-        }
+        Self::new_with_ports(name, time)
     }
 }
 impl Atomic for Transducer {
-    fn get_component(&self) -> &Component {
-        &self.component
-    }
-
-    fn get_component_mut(&mut self) -> &mut Component {
-        &mut self.component
-    }
-
-    fn lambda(&self) {
-        // Safety: adding message on atomic model's output port at lambda
-        unsafe { self.output.add_value(true) };
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, true);
     }
 
     fn delta_int(&mut self) {
@@ -154,15 +107,13 @@ impl Atomic for Transducer {
         println!("TRANSDUCER FINISHED");
     }
 
-    fn delta_ext(&mut self, e: f64) {
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
         self.sigma -= e;
         let t = self.component.get_t_last() + e;
-        // Safety: reading messages on atomic model's input port at delta_ext
-        for job in unsafe { self.input_g.get_values() }.iter() {
+        for job in input.get_values(&self.input_g).iter() {
             println!("generator sent job {job} at time {t}");
         }
-        // Safety: reading messages on atomic model's input port at delta_ext
-        for (job, time) in unsafe { self.input_p.get_values() }.iter() {
+        for (job, time) in input.get_values(&self.input_p).iter() {
             println!("processor processed job {job} after {time} seconds at time {t}");
         }
     }
@@ -200,16 +151,21 @@ fn create_gpt(period: f64, time: f64, observation: f64) -> Coupled {
     let generator = Generator::new("generator", period);
     let processor = Processor::new("processor", time);
     let transducer = Transducer::new("transducer", observation);
+    // Typed port identifiers must be taken before moving the components to the coupled model
+    let (gen_in, gen_out) = (generator.input.id(), generator.output.id());
+    let (proc_in, proc_out) = (processor.input.id(), processor.output.id());
+    let (trans_in_g, trans_in_p) = (transducer.input_g.id(), transducer.input_p.id());
+    let trans_out = transducer.output.id();
 
     let mut gpt = Coupled::new("gpt");
     gpt.add_component(Box::new(generator));
     gpt.add_component(Box::new(processor));
     gpt.add_component(Box::new(transducer));
 
-    gpt.add_ic("generator", "output", "processor", "input");
-    gpt.add_ic("generator", "output", "transducer", "input_g");
-    gpt.add_ic("processor", "output", "transducer", "input_p");
-    gpt.add_ic("transducer", "output", "generator", "input");
+    gpt.add_typed_ic(&gen_out, &proc_in);
+    gpt.add_typed_ic(&gen_out, &trans_in_g);
+    gpt.add_typed_ic(&proc_out, &trans_in_p);
+    gpt.add_typed_ic(&trans_out, &gen_in);
 
     gpt
 }
@@ -0,0 +1,14 @@
+mod cell;
+mod space;
+
+pub use cell::{Cell, Delay, Neighbors};
+pub use space::{Border, CellSpace, Neighborhood};
+
+use crate::DynRef;
+use std::fmt::Debug;
+
+/// Helper trait for the states of Cell-DEVS cells.
+/// The ports of cells are traced, so states must implement [`Debug`].
+pub trait CellState: DynRef + Clone + Debug + PartialEq {}
+
+impl<S: DynRef + Clone + Debug + PartialEq> CellState for S {}
@@ -0,0 +1,315 @@
+use super::CellState;
+use crate::modeling::*;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Local rule of a cell. It computes the next state of a cell from its current state and the
+/// state of its neighbors.
+pub(super) type Rule<S, const D: usize> = Arc<dyn Fn(&S, &Neighbors<S, D>) -> S + Send + Sync>;
+
+/// Delay of a cell. It is the time between the computation of a new state and its output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Delay {
+    /// All the state changes are sent after the delay, in the order in which they were computed.
+    Transport(f64),
+    /// A pending state change is discarded if a different state is computed before the delay expires.
+    Inertial(f64),
+}
+
+impl Delay {
+    /// Returns the duration of the delay.
+    #[inline]
+    pub fn duration(&self) -> f64 {
+        match self {
+            Self::Transport(d) | Self::Inertial(d) => *d,
+        }
+    }
+}
+
+/// Last known states of the neighbors of a cell, indexed by their offset relative to the cell
+/// (e.g., `[-1, 0]` is the neighbor to the left of a cell in a 2D grid).
+#[derive(Debug)]
+pub struct Neighbors<'a, S, const D: usize>(&'a [([isize; D], S)]);
+
+impl<'a, S, const D: usize> Neighbors<'a, S, D> {
+    /// Returns the state of the neighbor with the provided offset (if any).
+    pub fn get(&self, offset: [isize; D]) -> Option<&'a S> {
+        self.0.iter().find(|(o, _)| *o == offset).map(|(_, s)| s)
+    }
+
+    /// Returns an iterator over the offsets and states of all the neighbors.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a [isize; D], &'a S)> {
+        self.0.iter().map(|(o, s)| (o, s))
+    }
+
+    /// Returns the number of neighbors whose state satisfies a predicate.
+    pub fn count(&self, f: impl Fn(&S) -> bool) -> usize {
+        self.0.iter().filter(|(_, s)| f(s)).count()
+    }
+
+    /// Returns the number of neighbors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the cell has no neighbors.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Atomic model of a Cell-DEVS cell. `S` is the type of the state of the cell,
+/// and `D` is the number of dimensions of the cell space.
+///
+/// Cells receive the state of their neighbors through the `neighbors` input port.
+/// Messages are tuples with the offset of the neighbor relative to the cell and its new state.
+/// Every time a cell receives messages or changes its state, it computes its next state with
+/// its local rule. If the next state is different from the current state, it is sent through
+/// the `output` port after the [`Delay`] of the cell. Cells also compute their next state
+/// when the simulation starts.
+///
+/// Cells are usually created and coupled by a [`CellSpace`](super::CellSpace). Custom cell spaces
+/// can be built by coupling cells with [`Coupled::add_ic_map`], so messages are tagged with offsets:
+///
+/// ```
+/// use xdevs::celldevs::{Cell, Delay, Neighbors};
+/// use xdevs::modeling::Coupled;
+///
+/// // Cells copy the state of their left neighbor (if any)
+/// let rule = |state: &u8, neighbors: &Neighbors<u8, 1>| *neighbors.get([-1]).unwrap_or(state);
+/// let left = Cell::new("left", [0], 1, Delay::Transport(1.), rule);
+/// let mut right = Cell::new("right", [1], 0, Delay::Transport(1.), rule);
+/// right.set_neighbor([-1], *left.state());
+///
+/// let mut coupled = Coupled::new("space");
+/// coupled.add_component(Box::new(left));
+/// coupled.add_component(Box::new(right));
+/// coupled.add_ic_map("left", "output", "right", "neighbors", |s: &u8| Some(([-1isize], *s)));
+/// ```
+pub struct Cell<S: CellState, const D: usize> {
+    component: Component,
+    input: InPort<([isize; D], S)>,
+    output: OutPort<S>,
+    /// Coordinates of the cell in the cell space.
+    coords: [usize; D],
+    /// Current state of the cell.
+    state: S,
+    /// Last known states of the neighbors of the cell.
+    neighbors: Vec<([isize; D], S)>,
+    /// Delay of the cell.
+    delay: Delay,
+    /// Local rule of the cell.
+    rule: Rule<S, D>,
+    /// Time elapsed since the simulation started.
+    clock: f64,
+    /// Pending state changes, together with the time at which they are sent.
+    pending: VecDeque<(f64, S)>,
+}
+
+impl<S: CellState, const D: usize> Cell<S, D> {
+    /// Creates a new cell with the provided coordinates, initial state, delay, and local rule.
+    /// The cell has no neighbors until they are added with [`Cell::set_neighbor`].
+    /// This method panics if the delay is negative.
+    pub fn new(
+        name: &str,
+        coords: [usize; D],
+        state: S,
+        delay: Delay,
+        rule: impl Fn(&S, &Neighbors<S, D>) -> S + Send + Sync + 'static,
+    ) -> Self {
+        Self::with_rule(name, coords, state, delay, Arc::new(rule))
+    }
+
+    /// Creates a new cell with a local rule that may be shared with other cells.
+    pub(super) fn with_rule(
+        name: &str,
+        coords: [usize; D],
+        state: S,
+        delay: Delay,
+        rule: Rule<S, D>,
+    ) -> Self {
+        if delay.duration() < 0. {
+            panic!("cell delay must not be negative");
+        }
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("neighbors");
+        let output = component.add_traced_out_port("output");
+        Self {
+            component,
+            input,
+            output,
+            coords,
+            state,
+            neighbors: Vec::new(),
+            delay,
+            rule,
+            clock: 0.,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the coordinates of the cell.
+    #[inline]
+    pub fn coords(&self) -> [usize; D] {
+        self.coords
+    }
+
+    /// Returns the current state of the cell.
+    #[inline]
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Sets the last known state of the neighbor with the provided offset.
+    /// If the cell did not have a neighbor with this offset, it is added to its neighborhood.
+    pub fn set_neighbor(&mut self, offset: [isize; D], state: S) {
+        update_neighbor(&mut self.neighbors, offset, state);
+    }
+
+    /// Computes the next state of the cell and schedules it according to the delay of the cell.
+    fn evaluate(&mut self) {
+        let next = (self.rule)(&self.state, &Neighbors(&self.neighbors));
+        match self.delay {
+            Delay::Transport(d) => {
+                let last = self.pending.back().map_or(&self.state, |(_, s)| s);
+                if next != *last {
+                    self.pending.push_back((self.clock + d, next));
+                }
+            }
+            Delay::Inertial(d) => {
+                if next == self.state {
+                    self.pending.clear();
+                } else if self.pending.front().map(|(_, s)| s) != Some(&next) {
+                    self.pending.clear();
+                    self.pending.push_back((self.clock + d, next));
+                }
+            }
+        }
+    }
+
+    /// Applies the next pending state change.
+    fn apply_next(&mut self) {
+        let (t, state) = self.pending.pop_front().unwrap();
+        self.clock = t;
+        self.state = state;
+    }
+
+    /// Updates the last known states of the neighbors with the received messages.
+    fn read_neighbors(&mut self, input: &InputCtx) {
+        for (offset, state) in input.get_values(&self.input) {
+            update_neighbor(&mut self.neighbors, *offset, state.clone());
+        }
+    }
+}
+
+/// Sets the state of the neighbor with the provided offset, adding it if it does not exist.
+fn update_neighbor<S, const D: usize>(
+    neighbors: &mut Vec<([isize; D], S)>,
+    offset: [isize; D],
+    state: S,
+) {
+    match neighbors.iter_mut().find(|(o, _)| *o == offset) {
+        Some((_, s)) => *s = state,
+        None => neighbors.push((offset, state)),
+    }
+}
+
+impl<S: CellState, const D: usize> HasComponent for Cell<S, D> {
+    type Time = f64;
+
+    #[inline]
+    fn get_component(&self) -> &Component {
+        &self.component
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component {
+        &mut self.component
+    }
+}
+
+impl<S: CellState, const D: usize> Atomic for Cell<S, D> {
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.pending.clear();
+        self.evaluate();
+    }
+
+    fn lambda(&self, output: &OutputCtx) {
+        if let Some((_, state)) = self.pending.front() {
+            output.add_value(&self.output, state.clone());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.apply_next();
+        self.evaluate();
+    }
+
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+        self.clock += e;
+        self.read_neighbors(input);
+        self.evaluate();
+    }
+
+    /// The state change and the messages are processed before computing the next state only once.
+    fn delta_conf(&mut self, input: &InputCtx) {
+        self.apply_next();
+        self.read_neighbors(input);
+        self.evaluate();
+    }
+
+    fn ta(&self) -> f64 {
+        self.pending
+            .front()
+            .map_or(f64::INFINITY, |(t, _)| (t - self.clock).max(0.))
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        self.delay.duration() == 0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_delay() {
+        // The left cell toggles its state every second, and the right cell copies it
+        let simulate = |delay| {
+            let rule = |state: &bool, neighbors: &Neighbors<bool, 1>| match neighbors.get([-1]) {
+                Some(left) => *left,
+                None => !state,
+            };
+            let left = Cell::new("left", [0], false, Delay::Transport(1.), rule);
+            let mut right = Cell::new("right", [1], false, delay, rule);
+            right.set_neighbor([-1], false);
+            assert_eq!([1], right.coords());
+
+            let mut coupled = Coupled::new("space");
+            coupled.add_out_port::<bool>("output");
+            coupled.add_component(Box::new(left));
+            coupled.add_component(Box::new(right));
+            coupled.add_ic_map("left", "output", "right", "neighbors", |s: &bool| {
+                Some(([-1isize], *s))
+            });
+            coupled.add_eoc("right", "output", "output");
+            // The neighbors port of the left cell is left unconnected on purpose
+            let mut simulator = RootCoordinator::unvalidated(coupled);
+            simulator.collect_output::<bool>("output");
+            simulator.simulate(5.5);
+            simulator.get_output::<bool>("output").unwrap().to_vec()
+        };
+
+        // With transport delays, the right cell reproduces all the changes
+        let expected = vec![(3., true), (4., false), (5., true)];
+        assert_eq!(expected, simulate(Delay::Transport(2.)));
+        // With inertial delays, changes are preempted before the delay expires
+        assert!(simulate(Delay::Inertial(2.)).is_empty());
+    }
+}
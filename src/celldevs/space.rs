@@ -0,0 +1,305 @@
+use super::cell::{Cell, Delay, Neighbors, Rule};
+use super::CellState;
+use crate::modeling::Coupled;
+use std::sync::Arc;
+
+/// Neighborhood of the cells of a cell space. `D` is the number of dimensions of the cell space.
+/// Cells are never neighbors of themselves.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Neighborhood<const D: usize> {
+    /// Cells whose coordinates differ by at most the range in every dimension.
+    Moore(usize),
+    /// Cells whose Manhattan distance is at most the range.
+    VonNeumann(usize),
+    /// Cells with the provided offsets.
+    Custom(Vec<[isize; D]>),
+}
+
+impl<const D: usize> Neighborhood<D> {
+    /// Returns the offsets of the neighbors of a cell.
+    pub fn offsets(&self) -> Vec<[isize; D]> {
+        let (range, is_moore) = match self {
+            Self::Moore(range) => (*range as isize, true),
+            Self::VonNeumann(range) => (*range as isize, false),
+            Self::Custom(offsets) => return offsets.clone(),
+        };
+        let mut offsets = Vec::new();
+        let mut offset = [-range; D];
+        loop {
+            let distance: isize = offset.iter().map(|x| x.abs()).sum();
+            if distance > 0 && (is_moore || distance <= range) {
+                offsets.push(offset);
+            }
+            // we move to the next offset, as if it was a counter
+            let mut d = 0;
+            loop {
+                if d == D {
+                    return offsets;
+                }
+                if offset[d] < range {
+                    offset[d] += 1;
+                    break;
+                }
+                offset[d] = -range;
+                d += 1;
+            }
+        }
+    }
+}
+
+/// Behavior of the neighborhood of the cells in the borders of a cell space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Border<S> {
+    /// Neighbors outside the cell space are ignored.
+    Open,
+    /// The cell space wraps around its borders (i.e., it is a torus).
+    Wrapped,
+    /// Neighbors outside the cell space have a fixed state.
+    Fixed(S),
+}
+
+/// Cell-DEVS coupled model with a grid of [`Cell`]s of any number of dimensions.
+///
+/// Every cell is coupled to its neighbors according to the neighborhood and the border of the
+/// cell space. Cells are named after their coordinates (see [`CellSpace::cell_name`]).
+/// All the state changes are sent through the `output` port of the coupled model, together
+/// with the coordinates of the cell.
+pub struct CellSpace {
+    pub coupled: Coupled,
+}
+
+impl CellSpace {
+    /// Creates a new cell space with the provided shape. All the cells have the same
+    /// neighborhood, delay, and local rule. Their initial state is given by `init`.
+    ///
+    /// This method panics if the delay is negative, or if the grid is too small for wrapped borders
+    /// (i.e., a dimension has no more than twice as many cells as the range of the neighborhood in
+    /// that dimension, so a cell could be neighbor of another cell with more than one offset).
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<S: CellState, const D: usize>(
+        name: &str,
+        shape: [usize; D],
+        neighborhood: &Neighborhood<D>,
+        border: Border<S>,
+        delay: Delay,
+        rule: impl Fn(&S, &Neighbors<S, D>) -> S + Send + Sync + 'static,
+        init: impl Fn([usize; D]) -> S,
+    ) -> Self {
+        let offsets = neighborhood.offsets();
+        if let Border::Wrapped = border {
+            for d in 0..D {
+                let range = offsets.iter().map(|o| o[d].unsigned_abs()).max();
+                let range = range.unwrap_or(0);
+                if range > 0 && 2 * range >= shape[d] {
+                    panic!(
+                        "cell space is too small for wrapped borders: dimension {d} has {} cells, \
+                         but the neighborhood requires at least {}",
+                        shape[d],
+                        2 * range + 1
+                    );
+                }
+            }
+        }
+        let rule: Rule<S, D> = Arc::new(rule);
+        let coords: Vec<[usize; D]> = (0..shape.iter().product())
+            .map(|i| Self::coords(i, shape))
+            .collect();
+        let mut cells: Vec<Cell<S, D>> = coords
+            .iter()
+            .map(|&c| Cell::with_rule(&Self::cell_name(c), c, init(c), delay, rule.clone()))
+            .collect();
+        // We set the initial state of the neighbors of every cell and the couplings between them
+        let mut links = Vec::new();
+        for (i, &c) in coords.iter().enumerate() {
+            for &offset in offsets.iter() {
+                match Self::neighbor(c, offset, shape, &border) {
+                    Some(j) => {
+                        let state = cells[j].state().clone();
+                        cells[i].set_neighbor(offset, state);
+                        links.push((j, i, offset));
+                    }
+                    None => {
+                        if let Border::Fixed(state) = &border {
+                            cells[i].set_neighbor(offset, state.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut coupled = Coupled::new(name);
+        coupled.add_out_port::<([usize; D], S)>("output");
+        for (cell, c) in cells.into_iter().zip(coords.iter().copied()) {
+            let cell_name = Self::cell_name(c);
+            coupled.add_component(Box::new(cell));
+            coupled.add_eoc_map(&cell_name, "output", "output", move |s: &S| {
+                Some((c, s.clone()))
+            });
+        }
+        for (j, i, offset) in links {
+            let (from, to) = (Self::cell_name(coords[j]), Self::cell_name(coords[i]));
+            coupled.add_ic_map(&from, "output", &to, "neighbors", move |s: &S| {
+                Some((offset, s.clone()))
+            });
+        }
+        Self { coupled }
+    }
+
+    /// Returns the name of the cell with the provided coordinates (e.g., `cell_1_2`).
+    pub fn cell_name<const D: usize>(coords: [usize; D]) -> String {
+        let coords: Vec<String> = coords.iter().map(|x| x.to_string()).collect();
+        format!("cell_{}", coords.join("_"))
+    }
+
+    /// Returns the coordinates of the cell with the provided index.
+    /// The first dimension is the one that changes faster.
+    fn coords<const D: usize>(mut index: usize, shape: [usize; D]) -> [usize; D] {
+        let mut coords = [0; D];
+        for d in 0..D {
+            coords[d] = index % shape[d];
+            index /= shape[d];
+        }
+        coords
+    }
+
+    /// Returns the index of the neighbor of a cell with the provided offset.
+    /// It returns [`None`] if the neighbor is outside the cell space.
+    fn neighbor<S, const D: usize>(
+        coords: [usize; D],
+        offset: [isize; D],
+        shape: [usize; D],
+        border: &Border<S>,
+    ) -> Option<usize> {
+        let mut index = 0;
+        for d in (0..D).rev() {
+            let mut x = coords[d] as isize + offset[d];
+            let size = shape[d] as isize;
+            if !(0..size).contains(&x) {
+                match border {
+                    Border::Wrapped => x = x.rem_euclid(size),
+                    _ => return None,
+                }
+            }
+            index = index * shape[d] + x as usize;
+        }
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_neighborhood() {
+        assert_eq!(2, Neighborhood::<1>::Moore(1).offsets().len());
+        assert_eq!(8, Neighborhood::<2>::Moore(1).offsets().len());
+        assert_eq!(26, Neighborhood::<3>::Moore(1).offsets().len());
+        assert_eq!(24, Neighborhood::<2>::Moore(2).offsets().len());
+        assert_eq!(4, Neighborhood::<2>::VonNeumann(1).offsets().len());
+        assert_eq!(6, Neighborhood::<3>::VonNeumann(1).offsets().len());
+        assert_eq!(12, Neighborhood::<2>::VonNeumann(2).offsets().len());
+        let offsets = Neighborhood::<2>::VonNeumann(1).offsets();
+        for offset in [[-1, 0], [1, 0], [0, -1], [0, 1]] {
+            assert!(offsets.contains(&offset));
+        }
+        let custom = Neighborhood::Custom(vec![[-1, 0]]);
+        assert_eq!(vec![[-1, 0]], custom.offsets());
+        assert_eq!("cell_1_2", CellSpace::cell_name([1, 2]));
+    }
+
+    #[test]
+    fn test_fire_spread() {
+        // A fire starts in the first cell and spreads to its neighbors
+        let simulate = |border| {
+            let space = CellSpace::new(
+                "forest",
+                [5],
+                &Neighborhood::Moore(1),
+                border,
+                Delay::Transport(1.),
+                |burning: &bool, neighbors: &Neighbors<bool, 1>| {
+                    *burning || neighbors.count(|&b| b) > 0
+                },
+                |c| c == [0],
+            );
+            let mut simulator = RootCoordinator::new(space.coupled);
+            simulator.collect_output::<([usize; 1], bool)>("output");
+            simulator.simulate(10.);
+            simulator
+                .get_output::<([usize; 1], bool)>("output")
+                .unwrap()
+                .to_vec()
+        };
+
+        let expected: Vec<_> = (1..5).map(|i| (i as f64, ([i], true))).collect();
+        assert_eq!(expected, simulate(Border::Open));
+        let expected = vec![
+            (1., ([1], true)),
+            (1., ([4], true)),
+            (2., ([2], true)),
+            (2., ([3], true)),
+        ];
+        assert_eq!(expected, simulate(Border::Wrapped));
+        // Neighbors outside the forest may be burning or not
+        assert_eq!(expected, simulate(Border::Fixed(true)));
+        let expected: Vec<_> = (1..5).map(|i| (i as f64, ([i], true))).collect();
+        assert_eq!(expected, simulate(Border::Fixed(false)));
+    }
+
+    #[test]
+    fn test_game_of_life() {
+        // A blinker oscillates between a horizontal and a vertical line
+        let horizontal = [[1, 2], [2, 2], [3, 2]];
+        let space = CellSpace::new(
+            "life",
+            [5, 5],
+            &Neighborhood::Moore(1),
+            Border::Wrapped,
+            Delay::Inertial(1.),
+            |&alive: &bool, neighbors: &Neighbors<bool, 2>| {
+                matches!((alive, neighbors.count(|&a| a)), (true, 2) | (_, 3))
+            },
+            |c| horizontal.contains(&c),
+        );
+        assert_eq!(25, space.coupled.n_components());
+        assert_eq!(25 * 8, space.coupled.n_ics());
+        let mut simulator = RootCoordinator::new(space.coupled);
+        simulator.collect_output::<([usize; 2], bool)>("output");
+        simulator.simulate(2.5);
+        let mut output = simulator
+            .get_output::<([usize; 2], bool)>("output")
+            .unwrap()
+            .to_vec();
+        output.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let expected = vec![
+            (1., ([1, 2], false)),
+            (1., ([2, 1], true)),
+            (1., ([2, 3], true)),
+            (1., ([3, 2], false)),
+            (2., ([1, 2], true)),
+            (2., ([2, 1], false)),
+            (2., ([2, 3], false)),
+            (2., ([3, 2], true)),
+        ];
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "cell space is too small for wrapped borders: dimension 1 has 4 cells, \
+                               but the neighborhood requires at least 5"
+    )]
+    fn test_wrapped_too_small() {
+        CellSpace::new(
+            "life",
+            [5, 4],
+            &Neighborhood::Moore(2),
+            Border::Wrapped,
+            Delay::Inertial(1.),
+            |&alive: &bool, _: &Neighbors<bool, 2>| alive,
+            |_| false,
+        );
+    }
+}
@@ -5,6 +5,8 @@ pub mod homod;
 pub mod li;
 mod seeder;
 
+#[cfg(feature = "serde")]
+use crate::modeling::ModelRegistry;
 use atomic::DEVStoneAtomic;
 pub use hi::HI;
 pub use ho::HO;
@@ -28,3 +30,77 @@ pub struct TestProbe {
 
 #[cfg(test)]
 type SharedProbe = Arc<Mutex<TestProbe>>;
+
+/// Simulates a DEVStone model with a [`RootCoordinator`](crate::simulation::RootCoordinator)
+/// and the same model with a [`FlatCoordinator`](crate::simulation::FlatCoordinator).
+/// It checks that only the atomic models remain after flattening the model,
+/// and that both simulations execute the same steps and transitions.
+#[cfg(test)]
+fn assert_flat_equivalent(create: impl Fn(SharedProbe) -> crate::modeling::Coupled) {
+    use crate::simulation::{FlatCoordinator, RootCoordinator};
+
+    // DEVStone models leave some ports unconnected on purpose, so they are not validated
+    let root_probe = SharedProbe::default();
+    let mut root = RootCoordinator::unvalidated(create(root_probe.clone()));
+    let flat_probe = SharedProbe::default();
+    let mut flat = FlatCoordinator::unvalidated(create(flat_probe.clone()));
+    // The root models of DEVStone have no ports, so there are no external couplings left
+    assert_eq!(0, flat.n_eics());
+    assert_eq!(0, flat.n_eocs());
+
+    root.start();
+    flat.start();
+    loop {
+        let (t_root, t_flat) = (root.step(), flat.step());
+        assert_eq!(t_root, t_flat);
+        if t_root.is_none() {
+            break;
+        }
+    }
+    root.finish();
+    flat.finish();
+
+    let (root_probe, flat_probe) = (*root_probe.lock().unwrap(), *flat_probe.lock().unwrap());
+    // All the atomic models plus the seeder
+    assert_eq!(root_probe.n_atomics + 1, flat.n_components());
+    assert_eq!(root_probe.n_atomics, flat_probe.n_atomics);
+    assert_eq!(root_probe.n_internals, flat_probe.n_internals);
+    assert_eq!(root_probe.n_externals, flat_probe.n_externals);
+    assert_eq!(root_probe.n_events, flat_probe.n_events);
+}
+
+/// Parameters of DEVStone models in model descriptions.
+#[cfg(feature = "serde")]
+#[derive(Debug, serde::Deserialize)]
+pub struct DEVStoneParams {
+    pub width: usize,
+    pub depth: usize,
+    #[serde(default)]
+    pub int_delay: u64,
+    #[serde(default)]
+    pub ext_delay: u64,
+}
+
+/// Registers the `LI`, `HI`, `HO`, and `HOmod` DEVStone model types in a model registry.
+/// Their parameters are described by [`DEVStoneParams`].
+#[cfg(feature = "serde")]
+pub fn register(registry: &mut ModelRegistry) {
+    macro_rules! register_type {
+        ($id:literal, $model:ident) => {
+            registry.register($id, |_, p: DEVStoneParams| {
+                $model::create(
+                    p.width,
+                    p.depth,
+                    p.int_delay,
+                    p.ext_delay,
+                    #[cfg(test)]
+                    SharedProbe::default(),
+                )
+            });
+        };
+    }
+    register_type!("LI", LI);
+    register_type!("HI", HI);
+    register_type!("HO", HO);
+    register_type!("HOmod", HOmod);
+}
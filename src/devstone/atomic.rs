@@ -51,10 +51,6 @@ impl DEVStoneAtomic {
         ext_delay: u64,
         #[cfg(test)] probe: SharedProbe,
     ) -> Self {
-        let mut component = Component::new(name);
-        let input = component.add_in_port("input");
-        let output = component.add_out_port("output");
-
         let int_delay = match int_delay > 0 {
             true => Some(Duration::from_millis(int_delay)),
             false => None,
@@ -68,6 +64,9 @@ impl DEVStoneAtomic {
         let state = State::default();
         #[cfg(test)]
         let state = State::new(probe);
+        let mut component = Component::new(name);
+        let input = component.add_in_port("input");
+        let output = component.add_out_port("output");
         Self {
             component,
             input,
@@ -97,7 +96,9 @@ impl DEVStoneAtomic {
     }
 }
 
-impl Atomic for DEVStoneAtomic {
+impl HasComponent for DEVStoneAtomic {
+    type Time = f64;
+
     #[inline]
     fn get_component(&self) -> &Component {
         &self.component
@@ -107,7 +108,9 @@ impl Atomic for DEVStoneAtomic {
     fn get_component_mut(&mut self) -> &mut Component {
         &mut self.component
     }
+}
 
+impl Atomic for DEVStoneAtomic {
     #[cfg(test)]
     #[inline]
     fn stop(&mut self) {
@@ -115,9 +118,8 @@ impl Atomic for DEVStoneAtomic {
     }
 
     #[inline]
-    fn lambda(&self) {
-        // Safety: adding message on atomic model's output port at lambda
-        unsafe { self.output.add_value(self.state.n_events) };
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, self.state.n_events);
     }
 
     #[inline]
@@ -127,10 +129,9 @@ impl Atomic for DEVStoneAtomic {
         Self::sleep(&self.int_delay);
     }
 
-    fn delta_ext(&mut self, _e: f64) {
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
         self.state.n_externals += 1;
-        // Safety: reading messages on atomic model's input port at delta_ext
-        self.state.n_events += unsafe { self.input.get_values() }.len();
+        self.state.n_events += input.get_values(&self.input).len();
         self.sigma = 0.;
         Self::sleep(&self.ext_delay);
     }
@@ -109,6 +109,7 @@ impl HI {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devstone::assert_flat_equivalent;
     use crate::simulation::*;
     use std::sync::{Arc, Mutex};
 
@@ -137,7 +138,7 @@ mod tests {
             for depth in (1..50).step_by(5) {
                 let probe = Arc::new(Mutex::new(TestProbe::default()));
                 let coupled = HI::create(width, depth, 0, 0, probe.clone());
-                let mut simulator = RootCoordinator::new(coupled);
+                let mut simulator = RootCoordinator::unvalidated(coupled);
                 simulator.simulate(f64::INFINITY);
 
                 let x = probe.lock().unwrap();
@@ -151,4 +152,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_hi_flat() {
+        for width in (1..50).step_by(5) {
+            for depth in (1..50).step_by(5) {
+                assert_flat_equivalent(|probe| HI::create(width, depth, 0, 0, probe));
+            }
+        }
+    }
 }
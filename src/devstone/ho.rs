@@ -114,6 +114,7 @@ impl HO {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devstone::assert_flat_equivalent;
     use crate::simulation::*;
     use std::sync::{Arc, Mutex};
 
@@ -146,7 +147,7 @@ mod tests {
             for depth in (1..50).step_by(5) {
                 let probe = Arc::new(Mutex::new(TestProbe::default()));
                 let coupled = HO::create(width, depth, 0, 0, probe.clone());
-                let mut simulator = RootCoordinator::new(coupled);
+                let mut simulator = RootCoordinator::unvalidated(coupled);
                 simulator.simulate(f64::INFINITY);
 
                 let x = probe.lock().unwrap();
@@ -160,4 +161,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_ho_flat() {
+        for width in (1..50).step_by(5) {
+            for depth in (1..50).step_by(5) {
+                assert_flat_equivalent(|probe| HO::create(width, depth, 0, 0, probe));
+            }
+        }
+    }
 }
@@ -152,6 +152,7 @@ impl HOmod {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devstone::assert_flat_equivalent;
     use crate::simulation::*;
     use std::sync::{Arc, Mutex};
 
@@ -200,7 +201,7 @@ mod tests {
             for depth in (1..10).step_by(1) {
                 let probe = Arc::new(Mutex::new(TestProbe::default()));
                 let coupled = HOmod::create(width, depth, 0, 0, probe.clone());
-                let mut simulator = RootCoordinator::new(coupled);
+                let mut simulator = RootCoordinator::unvalidated(coupled);
                 simulator.simulate(f64::INFINITY);
 
                 let x = probe.lock().unwrap();
@@ -214,4 +215,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_homod_flat() {
+        for width in (1..10).step_by(1) {
+            for depth in (1..10).step_by(1) {
+                assert_flat_equivalent(|probe| HOmod::create(width, depth, 0, 0, probe));
+            }
+        }
+    }
 }
@@ -105,6 +105,7 @@ impl LI {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::devstone::assert_flat_equivalent;
     use crate::simulation::*;
     use std::sync::{Arc, Mutex};
 
@@ -122,7 +123,7 @@ mod tests {
             for depth in (1..50).step_by(5) {
                 let probe = Arc::new(Mutex::new(TestProbe::default()));
                 let coupled = LI::create(width, depth, 0, 0, probe.clone());
-                let mut simulator = RootCoordinator::new(coupled);
+                let mut simulator = RootCoordinator::unvalidated(coupled);
                 simulator.simulate(f64::INFINITY);
 
                 let x = probe.lock().unwrap();
@@ -136,4 +137,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_li_flat() {
+        for width in (1..50).step_by(5) {
+            for depth in (1..50).step_by(5) {
+                assert_flat_equivalent(|probe| LI::create(width, depth, 0, 0, probe));
+            }
+        }
+    }
 }
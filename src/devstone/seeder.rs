@@ -18,7 +18,9 @@ impl DEVStoneSeeder {
     }
 }
 
-impl Atomic for DEVStoneSeeder {
+impl HasComponent for DEVStoneSeeder {
+    type Time = f64;
+
     #[inline]
     fn get_component(&self) -> &Component {
         &self.component
@@ -28,11 +30,12 @@ impl Atomic for DEVStoneSeeder {
     fn get_component_mut(&mut self) -> &mut Component {
         &mut self.component
     }
+}
 
+impl Atomic for DEVStoneSeeder {
     #[inline]
-    fn lambda(&self) {
-        // Safety: adding message on atomic model's output port at lambda
-        unsafe { self.output.add_value(0) };
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, 0);
     }
 
     #[inline]
@@ -41,7 +44,7 @@ impl Atomic for DEVStoneSeeder {
     }
 
     #[inline]
-    fn delta_ext(&mut self, _e: f64) {
+    fn delta_ext(&mut self, _e: f64, _input: &InputCtx) {
         self.sigma = f64::INFINITY;
     }
 
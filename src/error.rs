@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// Errors that may occur when building DEVS models.
+/// Fallible methods such as [`crate::modeling::Coupled::try_add_ic`] return these errors,
+/// while their infallible counterparts (e.g., [`crate::modeling::Coupled::add_ic`]) panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The component already contains an input port with the same name.
+    DuplicateInPort { component: String, port: String },
+    /// The component already contains an output port with the same name.
+    DuplicateOutPort { component: String, port: String },
+    /// The coupled model already contains a component with the same name.
+    DuplicateComponent { coupled: String, component: String },
+    /// The coupled model does not contain the component.
+    ComponentNotFound { coupled: String, component: String },
+    /// The component does not contain the input port.
+    InPortNotFound { component: String, port: String },
+    /// The component does not contain the output port.
+    OutPortNotFound { component: String, port: String },
+    /// The port does not belong to the coupled model nor to any of its components.
+    PortNotInModel { coupled: String },
+    /// The message types of the ports of a coupling do not match.
+    IncompatiblePorts(Box<PortMismatch>),
+    /// The coupled model already contains the coupling.
+    DuplicateCoupling {
+        component_from: String,
+        port_from: String,
+        component_to: String,
+        port_to: String,
+    },
+    /// The coupled model does not contain the coupling.
+    CouplingNotFound {
+        component_from: String,
+        port_from: String,
+        component_to: String,
+        port_to: String,
+    },
+    /// The type of the component is not registered.
+    UnknownComponentType {
+        component: String,
+        component_id: String,
+    },
+    /// The parameters of the component are not valid for its type.
+    InvalidParameters { component: String, message: String },
+    /// The message type of the port cannot be determined.
+    UnknownPortType { component: String, port: String },
+    /// The description of the model is not valid.
+    InvalidDescription(String),
+}
+
+/// Description of a coupling between ports with different message types.
+/// It is boxed in [`Error::IncompatiblePorts`] to keep the size of [`Error`] small.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortMismatch {
+    pub component_from: String,
+    pub port_from: String,
+    pub component_to: String,
+    pub port_to: String,
+    /// Message type of the receiving port.
+    pub expected: &'static str,
+    /// Message type of the sending port.
+    pub found: &'static str,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateInPort { component, port } => {
+                write!(f, "component {component} already contains input port {port}")
+            }
+            Self::DuplicateOutPort { component, port } => {
+                write!(f, "component {component} already contains output port {port}")
+            }
+            Self::DuplicateComponent { coupled, component } => {
+                write!(f, "coupled model {coupled} already contains component {component}")
+            }
+            Self::ComponentNotFound { coupled, component } => {
+                write!(f, "coupled model {coupled} does not contain component {component}")
+            }
+            Self::InPortNotFound { component, port } => {
+                write!(f, "component {component} does not contain input port {port}")
+            }
+            Self::OutPortNotFound { component, port } => {
+                write!(f, "component {component} does not contain output port {port}")
+            }
+            Self::PortNotInModel { coupled } => {
+                write!(f, "port does not belong to coupled model {coupled} nor its components")
+            }
+            Self::IncompatiblePorts(mismatch) => write!(
+                f,
+                "port {}.{} is not compatible with port {}.{} (expected messages of type {}, found {})",
+                mismatch.component_from,
+                mismatch.port_from,
+                mismatch.component_to,
+                mismatch.port_to,
+                mismatch.expected,
+                mismatch.found
+            ),
+            Self::DuplicateCoupling {
+                component_from,
+                port_from,
+                component_to,
+                port_to,
+            } => write!(
+                f,
+                "coupling from {component_from}.{port_from} to {component_to}.{port_to} already exists"
+            ),
+            Self::CouplingNotFound {
+                component_from,
+                port_from,
+                component_to,
+                port_to,
+            } => write!(
+                f,
+                "coupling from {component_from}.{port_from} to {component_to}.{port_to} does not exist"
+            ),
+            Self::UnknownComponentType {
+                component,
+                component_id,
+            } => write!(f, "component {component} has unknown type {component_id}"),
+            Self::InvalidParameters { component, message } => {
+                write!(f, "invalid parameters for component {component}: {message}")
+            }
+            Self::UnknownPortType { component, port } => {
+                write!(f, "unknown message type of port {component}.{port}")
+            }
+            Self::InvalidDescription(message) => write!(f, "invalid model description: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Result type of fallible model-building methods.
+pub type Result<T> = std::result::Result<T, Error>;
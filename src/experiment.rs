@@ -0,0 +1,252 @@
+use crate::modeling::Time;
+use crate::simulation::{RootCoordinator, Simulator};
+#[cfg(feature = "par_experiment")]
+use rayon::prelude::*;
+
+/// Returns all the combinations of two lists of parameters (i.e., their Cartesian product).
+/// Grids of more dimensions can be built by nesting calls (e.g., `grid(&grid(&a, &b), &c)`).
+pub fn grid<A: Clone, B: Clone>(a: &[A], b: &[B]) -> Vec<(A, B)> {
+    a.iter()
+        .flat_map(|x| b.iter().map(move |y| (x.clone(), y.clone())))
+        .collect()
+}
+
+/// Simulation experiment with several parameter points and replications.
+///
+/// Every replication of every parameter point is simulated in its own [`RootCoordinator`].
+/// The seed of a run only depends on the master seed of the experiment and the replication,
+/// so all the parameter points share the same seeds (i.e., common random numbers).
+/// If the `rand` feature is activated, the seed of a run is the master seed of its root coordinator
+/// (see [`RootCoordinator::set_seed`]).
+///
+/// If the feature `par_experiment` is activated, runs are executed in parallel.
+///
+/// ```
+/// use xdevs::experiment::Experiment;
+/// use xdevs::modeling::Coupled;
+/// use xdevs::models::Generator;
+///
+/// // We count the messages sent by generators with different periods
+/// let mut experiment = Experiment::new([1., 2., 5.]);
+/// experiment.set_replications(2);
+/// let table = experiment.run(
+///     |&period, _seed| {
+///         let mut coupled = Coupled::new("root");
+///         coupled.add_out_port::<usize>("output");
+///         coupled.add_component(Box::new(Generator::periodic("generator", period, || 0usize)));
+///         coupled.add_eoc("generator", "output", "output");
+///         coupled
+///     },
+///     |simulator| {
+///         simulator.collect_output::<usize>("output");
+///         simulator.simulate(10.5);
+///         simulator.get_output::<usize>("output").unwrap().len()
+///     },
+/// );
+/// assert_eq!(6, table.runs.len());
+/// let means: Vec<f64> = table.summarize(|&n| n as f64).iter().map(|s| s.mean).collect();
+/// assert_eq!(vec![10., 5., 2.], means);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Experiment<P> {
+    /// Parameter points of the experiment.
+    params: Vec<P>,
+    /// Number of replications of every parameter point.
+    replications: usize,
+    /// Master seed of the experiment.
+    seed: u64,
+}
+
+impl<P: Clone + Send + Sync> Experiment<P> {
+    /// Creates a new experiment with the provided parameter points (see [`grid`]).
+    /// By default, every parameter point is simulated once, and the master seed is 0.
+    pub fn new(params: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            params: params.into_iter().collect(),
+            replications: 1,
+            seed: 0,
+        }
+    }
+
+    /// Sets the number of replications of every parameter point.
+    #[inline]
+    pub fn set_replications(&mut self, replications: usize) {
+        self.replications = replications;
+    }
+
+    /// Sets the master seed of the experiment.
+    /// The seed of the `i`-th replication is the master seed plus `i`.
+    #[inline]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Runs all the replications of all the parameter points of the experiment.
+    ///
+    /// For every run, `factory` creates the model from the parameter point and the seed of the run.
+    /// Then, `simulate` receives the root coordinator of the model, runs the simulation,
+    /// and returns the metrics of the run. Runs are sorted by parameter point and replication.
+    pub fn run<T, M, R, F, S>(&self, factory: F, simulate: S) -> Table<P, R>
+    where
+        T: Time,
+        M: Simulator<T>,
+        R: Send,
+        F: Fn(&P, u64) -> M + Send + Sync,
+        S: Fn(&mut RootCoordinator<M, T>) -> R + Send + Sync,
+    {
+        let runs: Vec<(usize, usize)> = (0..self.params.len())
+            .flat_map(|point| (0..self.replications).map(move |r| (point, r)))
+            .collect();
+        let execute = |&(point, replication): &(usize, usize)| {
+            let params = &self.params[point];
+            let seed = self.seed.wrapping_add(replication as u64);
+            let mut simulator = RootCoordinator::new(factory(params, seed));
+            #[cfg(feature = "rand")]
+            simulator.set_seed(seed);
+            let metrics = simulate(&mut simulator);
+            Run {
+                point,
+                params: params.clone(),
+                replication,
+                seed,
+                metrics,
+            }
+        };
+        #[cfg(feature = "par_experiment")]
+        let runs = runs.par_iter().map(execute).collect();
+        #[cfg(not(feature = "par_experiment"))]
+        let runs = runs.iter().map(execute).collect();
+        Table { runs }
+    }
+}
+
+/// Results of a run of an [`Experiment`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run<P, R> {
+    /// Index of the parameter point of the run.
+    pub point: usize,
+    /// Parameter point of the run.
+    pub params: P,
+    /// Index of the replication of the run.
+    pub replication: usize,
+    /// Seed of the run.
+    pub seed: u64,
+    /// Metrics returned by the run.
+    pub metrics: R,
+}
+
+/// Statistics of a metric over all the replications of a parameter point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary<P> {
+    /// Parameter point.
+    pub params: P,
+    /// Number of replications.
+    pub n: usize,
+    /// Mean of the metric.
+    pub mean: f64,
+    /// Sample standard deviation of the metric. It is 0 if there is only one replication.
+    pub std_dev: f64,
+}
+
+/// Table with the results of all the runs of an [`Experiment`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table<P, R> {
+    /// Runs of the experiment, sorted by parameter point and replication.
+    pub runs: Vec<Run<P, R>>,
+}
+
+impl<P: Clone, R> Table<P, R> {
+    /// Returns the statistics of a metric for every parameter point, in the order of the experiment.
+    /// `metric` extracts the metric from the results of a run.
+    pub fn summarize(&self, metric: impl Fn(&R) -> f64) -> Vec<Summary<P>> {
+        // runs are grouped by parameter point
+        let mut groups: Vec<(&Run<P, R>, Vec<f64>)> = Vec::new();
+        for run in self.runs.iter() {
+            match groups.last_mut() {
+                Some((first, values)) if first.point == run.point => {
+                    values.push(metric(&run.metrics))
+                }
+                _ => groups.push((run, vec![metric(&run.metrics)])),
+            }
+        }
+        groups
+            .into_iter()
+            .map(|(run, values)| {
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let std_dev = match values.len() > 1 {
+                    true => {
+                        let ss: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+                        (ss / (n - 1.)).sqrt()
+                    }
+                    false => 0.,
+                };
+                Summary {
+                    params: run.params.clone(),
+                    n: values.len(),
+                    mean,
+                    std_dev,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::models::Generator;
+
+    #[test]
+    fn test_grid() {
+        let expected = vec![(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')];
+        assert_eq!(expected, grid(&[1, 2], &['a', 'b']));
+        assert_eq!(8, grid(&grid(&[1, 2], &['a', 'b']), &[true, false]).len());
+    }
+
+    #[test]
+    fn test_experiment() {
+        let mut experiment = Experiment::new([1., 2.]);
+        experiment.set_replications(3);
+        experiment.set_seed(10);
+        // Generators send the seed of the run with the provided period
+        let table = experiment.run(
+            |&period, seed| {
+                let mut coupled = Coupled::new("root");
+                coupled.add_out_port::<u64>("output");
+                let generator = Generator::periodic("generator", period, move || seed);
+                coupled.add_component(Box::new(generator));
+                coupled.add_eoc("generator", "output", "output");
+                coupled
+            },
+            |simulator| {
+                simulator.collect_output::<u64>("output");
+                simulator.simulate(4.5);
+                let output = simulator.get_output::<u64>("output").unwrap();
+                (output.len(), output[0].1)
+            },
+        );
+
+        let points: Vec<_> = table.runs.iter().map(|run| run.point).collect();
+        assert_eq!(vec![0, 0, 0, 1, 1, 1], points);
+        let replications: Vec<_> = table.runs.iter().map(|run| run.replication).collect();
+        assert_eq!(vec![0, 1, 2, 0, 1, 2], replications);
+        for run in table.runs.iter() {
+            assert_eq!(experiment.params[run.point], run.params);
+            assert_eq!(10 + run.replication as u64, run.seed);
+            assert_eq!(run.seed, run.metrics.1);
+        }
+
+        let summary = table.summarize(|&(n, _)| n as f64);
+        let expected = [(1., 4.), (2., 2.)];
+        for (summary, (params, mean)) in summary.iter().zip(expected) {
+            assert_eq!(
+                (params, mean, 3, 0.),
+                (summary.params, summary.mean, summary.n, summary.std_dev)
+            );
+        }
+        let summary = table.summarize(|&(_, seed)| seed as f64);
+        assert_eq!((11., 1.), (summary[1].mean, summary[1].std_dev));
+    }
+}
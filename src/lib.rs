@@ -1,7 +1,18 @@
+// Allows the derive macros of `xdevs-derive` to refer to this crate as `::xdevs`.
+extern crate self as xdevs;
+
+pub mod celldevs;
 pub mod devstone;
+mod error;
+pub mod experiment;
 pub mod modeling;
+pub mod models;
+#[cfg(feature = "rand")]
+pub mod rng;
 pub mod simulation;
 
+pub use error::{Error, PortMismatch, Result};
+
 /// Helper trait for avoiding verbose trait constraints.
 #[cfg(not(feature = "par_any"))]
 pub trait DynRef: 'static {}
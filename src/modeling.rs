@@ -1,9 +1,24 @@
 pub mod atomic;
 pub mod component;
 pub mod coupled;
+mod dot;
 pub mod port;
+#[cfg(feature = "serde")]
+pub mod registry;
+#[cfg(feature = "serde")]
+pub mod snapshot;
+pub mod structure;
+pub mod time;
+pub mod validate;
 
-pub use atomic::Atomic;
+pub use atomic::{Atomic, HasComponent};
 pub use component::Component;
 pub use coupled::Coupled;
-pub use port::{InPort, OutPort};
+pub use port::{InPort, InPortId, InputCtx, OutPort, OutPortId, OutputCtx};
+#[cfg(feature = "serde")]
+pub use registry::ModelRegistry;
+#[cfg(feature = "serde")]
+pub use snapshot::{DynSnapshot, Snapshot};
+pub use structure::StructureHandle;
+pub use time::{Fixed, Time};
+pub use validate::Issue;
@@ -1,13 +1,49 @@
-use super::Component;
+#[cfg(feature = "serde")]
+use super::DynSnapshot;
+use super::{Component, InputCtx, OutputCtx, Time};
+
+/// Derive macro for the [`HasComponent`] trait of atomic models.
+/// It also generates a `new_with_ports` constructor that creates the component and the ports of the model.
+/// Ports are the fields marked with `#[in_port]` or `#[out_port]`. By default, they are named after their field,
+/// but a different name can be set with `#[in_port("name")]` or `#[out_port("name")]`.
+/// The component is the field marked with `#[component]` (or, otherwise, the field named `component`).
+/// The constructor receives the name of the model and the rest of fields, in declaration order.
+/// It is only available if the feature `derive` is activated.
+///
+/// ```
+/// use xdevs::modeling::*;
+///
+/// #[derive(Atomic)]
+/// struct Counter {
+///     component: Component,
+///     #[in_port("input")]
+///     input: InPort<usize>,
+///     #[out_port]
+///     output: OutPort<usize>,
+///     count: usize,
+/// }
+///
+/// let counter = Counter::new_with_ports("counter", 0);
+/// assert_eq!("counter", counter.get_component().get_name());
+/// ```
+#[cfg(feature = "derive")]
+pub use xdevs_derive::Atomic;
+
+/// Interface for DEVS models that comprise a [`Component`].
+/// It can be derived for atomic models with `#[derive(Atomic)]` (see the `derive` feature).
+pub trait HasComponent {
+    /// Type used for representing simulation times (e.g., [`f64`]).
+    type Time: Time;
 
-/// Interface for atomic DEVS models.
-pub trait Atomic {
     /// Returns reference to inner component.
-    fn get_component(&self) -> &Component;
+    fn get_component(&self) -> &Component<Self::Time>;
 
     /// Returns mutable reference to inner component.
-    fn get_component_mut(&mut self) -> &mut Component;
+    fn get_component_mut(&mut self) -> &mut Component<Self::Time>;
+}
 
+/// Interface for atomic DEVS models.
+pub trait Atomic: HasComponent {
     /// Method for performing any operation before simulating. By default, it does nothing.
     #[inline]
     fn start(&mut self) {}
@@ -17,26 +53,53 @@ pub trait Atomic {
     fn stop(&mut self) {}
 
     /// Output function of the atomic DEVS model. This is the only method where
-    /// implementers can safely manipulate their [`super::OutPort`] structs.
-    fn lambda(&self);
+    /// implementers can safely manipulate their [`super::OutPort`] structs via the [`OutputCtx`].
+    fn lambda(&self, output: &OutputCtx);
 
     /// Internal transition function of the atomic DEVS model.
     fn delta_int(&mut self);
 
     /// External transition function of the atomic DEVS model.
     /// `e` corresponds to the elapsed time since the last state transition of the model.
-    /// This is the only method where implementers can safely manipulate their [`super::InPort`] structs.
-    fn delta_ext(&mut self, e: f64);
+    /// Together with [`Atomic::delta_conf`], this is the only method where implementers
+    /// can safely manipulate their [`super::InPort`] structs via the [`InputCtx`].
+    fn delta_ext(&mut self, e: Self::Time, input: &InputCtx);
 
     /// Time advance function of the atomic DEVS model.
-    fn ta(&self) -> f64;
+    fn ta(&self) -> Self::Time;
 
     /// Confluent transition function of the atomic DEVS model.
     /// By default, it first triggers [`Atomic::delta_int`].
     /// Then, it triggers [`Atomic::delta_ext`] with the elapsed time set to 0.
     #[inline]
-    fn delta_conf(&mut self) {
+    fn delta_conf(&mut self, input: &InputCtx) {
         self.delta_int();
-        self.delta_ext(0.);
+        self.delta_ext(Self::Time::ZERO, input);
+    }
+
+    /// Returns `true` if the model may send messages in zero time after receiving a message
+    /// (i.e., [`Atomic::ta`] may return zero after [`Atomic::delta_ext`]).
+    /// It is used by [`super::Coupled::validate`] to detect algebraic loops.
+    /// By default, it returns `false`.
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        false
+    }
+
+    /// Returns a reference to the model as a [`DynSnapshot`] trait object.
+    /// Models that implement [`super::Snapshot`] must override this method to return `Some(self)`.
+    /// By default, it returns [`None`], and the state of the model is not saved in checkpoints.
+    #[cfg(feature = "serde")]
+    #[inline]
+    fn as_snapshot(&self) -> Option<&dyn DynSnapshot> {
+        None
+    }
+
+    /// Returns a mutable reference to the model as a [`DynSnapshot`] trait object.
+    /// Models that implement [`super::Snapshot`] must override this method to return `Some(self)`.
+    #[cfg(feature = "serde")]
+    #[inline]
+    fn as_snapshot_mut(&mut self) -> Option<&mut dyn DynSnapshot> {
+        None
     }
 }
@@ -1,16 +1,30 @@
+use super::coupled::port_address;
 use super::port::{Bag, InPort, OutPort, Port};
-use crate::DynRef;
+use super::Time;
+#[cfg(feature = "rand")]
+use crate::rng::{ModelRng, RngStream};
+use crate::simulation::SimObserver;
+use crate::{DynRef, Error, Result};
 use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Identifier of the next component. Identifiers start at 1, as 0 is reserved for ports without owner.
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
 /// DEVS component. Models must comprise a component to fulfill the [`crate::simulation::Simulator`] trait.
-pub struct Component {
+///
+/// `T` is the type used for representing simulation times. By default, it is [`f64`].
+pub struct Component<T: Time = f64> {
+    /// Unique identifier of the DEVS component. It is used for checking the owner of ports.
+    id: u64,
     /// Name of the DEVS component.
     name: String,
     /// Time of the last component state transition.
-    t_last: f64,
+    t_last: T,
     /// Time for the next component state transition.
-    t_next: f64,
+    t_next: T,
     /// Input ports map. Keys are the port IDs, and values correspond to the index of the port in `in_ports`.
     in_map: HashMap<String, usize>,
     /// Output ports map. Keys are the port IDs, and values correspond to the index of the port in `out_ports`.
@@ -19,22 +33,37 @@ pub struct Component {
     in_ports: Vec<Arc<dyn Port>>,
     /// Output port set of the DEVS component (serialized for better performance).
     out_ports: Vec<Arc<dyn Port>>,
+    /// Full path of the DEVS component and simulation observer (if any).
+    observer: Option<(String, Arc<dyn SimObserver<T>>)>,
+    /// Random number stream of the DEVS component (if any).
+    #[cfg(feature = "rand")]
+    rng: Option<RngStream>,
 }
 
-impl Component {
+impl<T: Time> Component<T> {
     /// It creates a new component with the provided name.
     pub fn new(name: &str) -> Self {
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             name: name.to_string(),
-            t_last: 0.,
-            t_next: f64::INFINITY,
+            t_last: T::ZERO,
+            t_next: T::INFINITY,
             in_map: HashMap::new(),
             out_map: HashMap::new(),
             in_ports: Vec::new(),
             out_ports: Vec::new(),
+            observer: None,
+            #[cfg(feature = "rand")]
+            rng: None,
         }
     }
 
+    /// Returns the unique identifier of the component.
+    #[inline]
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
     /// Returns name of the component.
     #[inline]
     pub fn get_name(&self) -> &str {
@@ -43,45 +72,176 @@ impl Component {
 
     /// Returns the time for the last component state transition.
     #[inline]
-    pub fn get_t_last(&self) -> f64 {
+    pub fn get_t_last(&self) -> T {
         self.t_last
     }
 
     /// Returns the time for the next component state transition.
     #[inline]
-    pub fn get_t_next(&self) -> f64 {
+    pub fn get_t_next(&self) -> T {
         self.t_next
     }
 
+    /// Returns the random number stream of the component.
+    /// The stream is derived from the master seed of the simulation and the full path of the component
+    /// (see [`RootCoordinator::set_seed`](crate::simulation::RootCoordinator::set_seed)).
+    /// This method panics if the component has not been added to a simulation yet.
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub fn rng(&mut self) -> &mut ModelRng {
+        match &mut self.rng {
+            Some(stream) => &mut stream.rng,
+            None => panic!("component {} has no random number stream", self.name),
+        }
+    }
+
+    /// Returns the random number stream of the component, together with its seed and full path.
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub(crate) fn get_rng_stream(&self) -> Option<&RngStream> {
+        self.rng.as_ref()
+    }
+
+    /// Sets the random number stream of the component.
+    #[cfg(feature = "rand")]
+    #[inline]
+    pub(crate) fn set_rng_stream(&mut self, rng: Option<RngStream>) {
+        self.rng = rng;
+    }
+
+    /// Sets the name of the component.
+    #[inline]
+    pub(crate) fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+
+    /// Returns the full path of the component and its simulation observer (if any).
+    #[inline]
+    pub(crate) fn get_observer(&self) -> Option<(&str, &dyn SimObserver<T>)> {
+        let (path, observer) = self.observer.as_ref()?;
+        Some((path, &**observer))
+    }
+
+    /// Returns the full path of the component and a new reference to its simulation observer (if any).
+    #[inline]
+    pub(crate) fn clone_observer(&self) -> Option<(String, Arc<dyn SimObserver<T>>)> {
+        self.observer.clone()
+    }
+
+    /// Sets the full path of the component and its simulation observer.
+    #[inline]
+    pub(crate) fn set_observer(&mut self, path: &str, observer: Option<Arc<dyn SimObserver<T>>>) {
+        self.observer = observer.map(|observer| (path.to_string(), observer));
+    }
+
     /// Sets the time for the for the last and next component state transitions.
     #[inline]
-    pub(crate) fn set_sim_t(&mut self, t_last: f64, t_next: f64) {
+    pub(crate) fn set_sim_t(&mut self, t_last: T, t_next: T) {
         self.t_last = t_last;
         self.t_next = t_next;
     }
 
-    /// Adds a new input port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `V` and returns a reference to it.
+    /// The port is not traced: simulation observers (e.g., [`crate::simulation::TraceWriter`])
+    /// only see the type of its messages, such as `<u32>`. To record the values of the messages,
+    /// use [`Component::add_traced_in_port`] instead.
     /// It panics if there is already an input port with the same name.
-    pub fn add_in_port<T: DynRef + Clone>(&mut self, name: &str) -> InPort<T> {
+    pub fn add_in_port<V: DynRef + Clone>(&mut self, name: &str) -> InPort<V> {
+        self.try_add_in_port(name).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new input port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an input port with the same name.
+    pub fn try_add_in_port<V: DynRef + Clone>(&mut self, name: &str) -> Result<InPort<V>> {
+        let bag = Bag::new(self.id);
+        self.add_dyn_in_port(name, bag.clone())?;
+        Ok(InPort(bag))
+    }
+
+    /// Adds a new traced input port of type `V` and returns a reference to it.
+    /// Simulation observers receive the [`Debug`] representation of the messages of traced ports
+    /// (see [`SimObserver::on_input`]). Other ports only show the type of their messages.
+    /// It panics if there is already an input port with the same name.
+    pub fn add_traced_in_port<V: DynRef + Clone + Debug>(&mut self, name: &str) -> InPort<V> {
+        self.try_add_traced_in_port(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new traced input port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an input port with the same name.
+    pub fn try_add_traced_in_port<V: DynRef + Clone + Debug>(
+        &mut self,
+        name: &str,
+    ) -> Result<InPort<V>> {
+        let bag = Bag::new_traced(self.id);
+        self.add_dyn_in_port(name, bag.clone())?;
+        Ok(InPort(bag))
+    }
+
+    /// Adds an existing input port of any type.
+    /// It returns an error if there is already an input port with the same name.
+    pub(crate) fn add_dyn_in_port(&mut self, name: &str, port: Arc<dyn Port>) -> Result<()> {
         if self.in_map.contains_key(name) {
-            panic!("component already contains input port with the name provided");
+            return Err(Error::DuplicateInPort {
+                component: self.name.clone(),
+                port: name.to_string(),
+            });
         }
         self.in_map.insert(name.to_string(), self.in_ports.len());
-        let bag = Bag::new();
-        self.in_ports.push(bag.clone());
-        InPort(bag)
+        self.in_ports.push(port);
+        Ok(())
+    }
+
+    /// Adds a new output port of type `V` and returns a reference to it.
+    /// The port is not traced: simulation observers (e.g., [`crate::simulation::TraceWriter`])
+    /// only see the type of its messages, such as `<u32>`. To record the values of the messages,
+    /// use [`Component::add_traced_out_port`] instead.
+    /// It panics if there is already an output port with the same name.
+    pub fn add_out_port<V: DynRef + Clone>(&mut self, name: &str) -> OutPort<V> {
+        self.try_add_out_port(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new output port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    pub fn try_add_out_port<V: DynRef + Clone>(&mut self, name: &str) -> Result<OutPort<V>> {
+        let bag = Bag::new(self.id);
+        self.add_dyn_out_port(name, bag.clone())?;
+        Ok(OutPort(bag))
     }
 
-    /// Adds a new output port of type `T` and returns a reference to it.
+    /// Adds a new traced output port of type `V` and returns a reference to it.
+    /// Simulation observers receive the [`Debug`] representation of the messages of traced ports
+    /// (see [`SimObserver::on_output`]). Other ports only show the type of their messages.
     /// It panics if there is already an output port with the same name.
-    pub fn add_out_port<T: DynRef + Clone>(&mut self, name: &str) -> OutPort<T> {
+    pub fn add_traced_out_port<V: DynRef + Clone + Debug>(&mut self, name: &str) -> OutPort<V> {
+        self.try_add_traced_out_port(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Adds a new traced output port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    pub fn try_add_traced_out_port<V: DynRef + Clone + Debug>(
+        &mut self,
+        name: &str,
+    ) -> Result<OutPort<V>> {
+        let bag = Bag::new_traced(self.id);
+        self.add_dyn_out_port(name, bag.clone())?;
+        Ok(OutPort(bag))
+    }
+
+    /// Adds an existing output port of any type.
+    /// It returns an error if there is already an output port with the same name.
+    pub(crate) fn add_dyn_out_port(&mut self, name: &str, port: Arc<dyn Port>) -> Result<()> {
         if self.out_map.contains_key(name) {
-            panic!("component already contains output port with the name provided");
+            return Err(Error::DuplicateOutPort {
+                component: self.name.clone(),
+                port: name.to_string(),
+            });
         }
         self.out_map.insert(name.to_string(), self.out_ports.len());
-        let bag = Bag::new();
-        self.out_ports.push(bag.clone());
-        OutPort(bag)
+        self.out_ports.push(port);
+        Ok(())
     }
 
     /// Returns `true` if all the input ports of the model are empty.
@@ -111,6 +271,74 @@ impl Component {
         Some(self.out_ports.get(i)?.clone())
     }
 
+    /// Returns the name of the input port with the given address.
+    /// If the component does not have any input port with this address, it returns [`None`].
+    pub(crate) fn get_in_port_name(&self, address: *const ()) -> Option<&str> {
+        port_name(&self.in_map, &self.in_ports, address)
+    }
+
+    /// Returns the name of the output port with the given address.
+    /// If the component does not have any output port with this address, it returns [`None`].
+    pub(crate) fn get_out_port_name(&self, address: *const ()) -> Option<&str> {
+        port_name(&self.out_map, &self.out_ports, address)
+    }
+
+    /// Returns a reference to an input port with the given name.
+    /// If the component does not have any input port with this name, it returns an error.
+    #[inline]
+    pub(crate) fn find_in_port(&self, port_name: &str) -> Result<Arc<dyn Port>> {
+        self.get_in_port(port_name)
+            .ok_or_else(|| Error::InPortNotFound {
+                component: self.name.clone(),
+                port: port_name.to_string(),
+            })
+    }
+
+    /// Returns a reference to an output port with the given name.
+    /// If the component does not have any output port with this name, it returns an error.
+    #[inline]
+    pub(crate) fn find_out_port(&self, port_name: &str) -> Result<Arc<dyn Port>> {
+        self.get_out_port(port_name)
+            .ok_or_else(|| Error::OutPortNotFound {
+                component: self.name.clone(),
+                port: port_name.to_string(),
+            })
+    }
+
+    /// Returns the names and references of all the input ports, sorted by creation order.
+    pub(crate) fn get_in_ports(&self) -> Vec<(String, Arc<dyn Port>)> {
+        sorted_ports(&self.in_map, &self.in_ports)
+    }
+
+    /// Returns the names and references of all the output ports, sorted by creation order.
+    pub(crate) fn get_out_ports(&self) -> Vec<(String, Arc<dyn Port>)> {
+        sorted_ports(&self.out_map, &self.out_ports)
+    }
+
+    /// Calls `f` for every message in the input ports, together with the name of the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed when implementing the [`crate::simulation::Simulator::transition`]
+    /// method to notify the [`SimObserver`] about the messages received by the component.
+    pub(crate) unsafe fn for_each_input(&self, f: &mut dyn FnMut(&str, &dyn Debug)) {
+        for (name, port) in self.get_in_ports() {
+            port.for_each_value(&mut |value| f(&name, value));
+        }
+    }
+
+    /// Calls `f` for every message in the output ports, together with the name of the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed when implementing the [`crate::simulation::Simulator::collection`]
+    /// method to notify the [`SimObserver`] about the messages sent by the component.
+    pub(crate) unsafe fn for_each_output(&self, f: &mut dyn FnMut(&str, &dyn Debug)) {
+        for (name, port) in self.get_out_ports() {
+            port.for_each_value(&mut |value| f(&name, value));
+        }
+    }
+
     /// Clears all the input ports of the model.
     ///
     /// # Safety
@@ -131,3 +359,74 @@ impl Component {
         self.out_ports.iter_mut().for_each(|p| p.clear());
     }
 }
+
+/// Returns the name of the port with the given address in a set of ports (if any).
+fn port_name<'a>(
+    map: &'a HashMap<String, usize>,
+    ports: &[Arc<dyn Port>],
+    address: *const (),
+) -> Option<&'a str> {
+    map.iter()
+        .find(|(_, &i)| port_address(&ports[i]) == address)
+        .map(|(name, _)| name.as_str())
+}
+
+/// Returns the names and references of a set of ports, sorted by creation order.
+fn sorted_ports(
+    map: &HashMap<String, usize>,
+    ports: &[Arc<dyn Port>],
+) -> Vec<(String, Arc<dyn Port>)> {
+    let mut names: Vec<_> = map.iter().collect();
+    names.sort_unstable_by_key(|(_, &i)| i);
+    names
+        .into_iter()
+        .map(|(name, &i)| (name.clone(), ports[i].clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_add_port() {
+        let mut component = Component::<f64>::new("atomic");
+        component.try_add_in_port::<usize>("input").unwrap();
+        component.try_add_out_port::<usize>("output").unwrap();
+
+        let duplicate_in = Error::DuplicateInPort {
+            component: "atomic".to_string(),
+            port: "input".to_string(),
+        };
+        let result = component.try_add_in_port::<usize>("input").map(|_| ());
+        assert_eq!(Err(duplicate_in.clone()), result);
+        // ports are identified by name, regardless of their type or whether they are traced
+        let result = component.try_add_traced_in_port::<f64>("input").map(|_| ());
+        assert_eq!(Err(duplicate_in), result);
+
+        let duplicate_out = Error::DuplicateOutPort {
+            component: "atomic".to_string(),
+            port: "output".to_string(),
+        };
+        let result = component.try_add_out_port::<bool>("output").map(|_| ());
+        assert_eq!(Err(duplicate_out.clone()), result);
+        let result = component
+            .try_add_traced_out_port::<usize>("output")
+            .map(|_| ());
+        assert_eq!(Err(duplicate_out), result);
+        assert_eq!(
+            "component atomic already contains output port output",
+            component
+                .try_add_out_port::<usize>("output")
+                .map(|_| ())
+                .unwrap_err()
+                .to_string()
+        );
+
+        // input and output ports have different namespaces
+        component.try_add_in_port::<usize>("output").unwrap();
+        component.try_add_out_port::<usize>("input").unwrap();
+        assert_eq!(2, component.get_in_ports().len());
+        assert_eq!(2, component.get_out_ports().len());
+    }
+}
@@ -1,16 +1,183 @@
-use super::port::Port;
-use super::{Component, InPort, OutPort};
-use crate::simulation::Simulator;
-use crate::DynRef;
+use super::port::{Chain, FilterMap, Port, Transform};
+use super::structure::{ChangeQueue, StructuralChange, StructureHandle};
+use super::{Component, InPort, InPortId, OutPort, OutPortId, Time};
+use crate::simulation::{visit_mut, CouplingInfo, Scheduler, Simulator};
+use crate::{DynRef, Error, PortMismatch, Result};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
-pub(crate) type Coupling = (Arc<dyn Port>, Arc<dyn Port>);
+/// DEVS coupling. It relates a sending port to a receiving port.
+#[derive(Clone)]
+pub(crate) struct Coupling {
+    /// Name of the sending component. It is [`None`] for the coupled model itself.
+    component_from: Option<String>,
+    /// Name of the sending port.
+    port_from: String,
+    /// Name of the receiving component. It is [`None`] for the coupled model itself.
+    component_to: Option<String>,
+    /// Name of the receiving port.
+    port_to: String,
+    /// Sending port.
+    p_from: Arc<dyn Port>,
+    /// Receiving port.
+    p_to: Arc<dyn Port>,
+    /// Transformation of the propagated messages (if any).
+    transform: Option<Arc<dyn Transform>>,
+}
+
+impl Coupling {
+    /// Propagates messages from the sending port to the receiving port.
+    /// If the coupling has a transformation, messages are transformed before being propagated.
+    /// It returns `false` if the receiving port did not receive any message.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a [`Coupled`] model when propagating
+    /// messages in its [`crate::simulation::Simulator`] trait implementation.
+    #[inline]
+    pub(crate) unsafe fn propagate(&self) -> bool {
+        if self.p_from.is_empty() {
+            return false;
+        }
+        match &self.transform {
+            Some(transform) => transform.propagate(&*self.p_from, &*self.p_to),
+            None => {
+                self.p_from.propagate(&*self.p_to);
+                true
+            }
+        }
+    }
+
+    /// Returns the addresses of the sending and receiving ports.
+    #[inline]
+    pub(crate) fn port_addresses(&self) -> (*const (), *const ()) {
+        (port_address(&self.p_from), port_address(&self.p_to))
+    }
+
+    /// Returns a description of the coupling.
+    #[inline]
+    pub(crate) fn info(&self) -> CouplingInfo<'_> {
+        CouplingInfo {
+            component_from: self.component_from.as_deref(),
+            port_from: &self.port_from,
+            component_to: self.component_to.as_deref(),
+            port_to: &self.port_to,
+        }
+    }
+}
+
+/// Returns the address of a port. It is used for identifying ports.
+#[inline]
+pub(crate) fn port_address(port: &Arc<dyn Port>) -> *const () {
+    Arc::as_ptr(port).cast()
+}
+
+/// Position of a coupling in the propagation order of its coupled model.
+/// EOCs and ICs (propagated during the collection) come before EICs (propagated during
+/// the transition), and couplings of the same kind are sorted by insertion order.
+type Hop = (u8, usize);
+
+/// Couplings between ports, from the address of the sending port to the receiving ports,
+/// the transformations of the messages (if any), and the positions of the couplings.
+type Links = HashMap<*const (), Vec<(Arc<dyn Port>, Option<Arc<dyn Transform>>, Hop)>>;
+
+/// End of a chain of couplings: the address of the receiving port, the combined transformation
+/// of the messages (if any), and the positions of the couplings of the chain from last to first.
+/// Sorting chains that end in the same port by their positions reproduces the order in which
+/// the port receives messages in hierarchical simulations.
+type LinkEnd = (*const (), Option<Arc<dyn Transform>>, Vec<Hop>);
+
+/// Tie-breaking function of coupled models in Classic DEVS mode (see [`Coupled::set_select`]).
+type Select = Box<dyn Fn(&[&str]) -> usize + Send + Sync>;
+
+/// Returns the addresses of all the ports that receive messages from a given port,
+/// either directly or through a chain of couplings. Only the ends of the chains are returned,
+/// together with the combined transformation of the messages along the chain (if any)
+/// and the positions of its couplings (see [`LinkEnd`]).
+fn resolve_links(port: &Arc<dyn Port>, links: &Links) -> Vec<LinkEnd> {
+    let mut ends = Vec::new();
+    let mut stack: Vec<_> = match links.get(&port_address(port)) {
+        Some(ports) => ports
+            .iter()
+            .rev()
+            .map(|link| (link, Vec::new(), Vec::new()))
+            .collect(),
+        None => return ends,
+    };
+    while let Some(((port, transform, hop), mut transforms, mut hops)) = stack.pop() {
+        transforms.extend(transform.clone());
+        hops.push(*hop);
+        let address = port_address(port);
+        match links.get(&address) {
+            Some(ports) => stack.extend(
+                ports
+                    .iter()
+                    .rev()
+                    .map(|link| (link, transforms.clone(), hops.clone())),
+            ),
+            None => {
+                hops.reverse();
+                ends.push((address, Chain::combine(transforms), hops));
+            }
+        }
+    }
+    ends
+}
+
+/// Returns an error if the ports of a coupling are not compatible.
+/// If the coupling has a transformation, the sending port must be compatible with its input
+/// and the receiving port must be compatible with its output.
+/// The coupling is described by the names of its sending component and port,
+/// and the names of its receiving component and port.
+fn check_compatible(
+    p_from: &dyn Port,
+    p_to: &dyn Port,
+    transform: Option<&dyn Transform>,
+    coupling: (&str, &str, &str, &str),
+) -> Result<()> {
+    let (expected, found) = match transform {
+        None if p_from.is_compatible(p_to) => return Ok(()),
+        None => (p_to.type_name(), p_from.type_name()),
+        Some(t) if !t.accepts(p_from) => (t.input_type_name(), p_from.type_name()),
+        Some(t) if !t.produces(p_to) => (p_to.type_name(), t.output_type_name()),
+        Some(_) => return Ok(()),
+    };
+    let (component_from, port_from, component_to, port_to) = coupling;
+    Err(Error::IncompatiblePorts(Box::new(PortMismatch {
+        component_from: component_from.to_string(),
+        port_from: port_from.to_string(),
+        component_to: component_to.to_string(),
+        port_to: port_to.to_string(),
+        expected,
+        found,
+    })))
+}
+
+/// Returns an error for a coupling that already exists.
+fn duplicate_coupling(coupling: (&str, &str, &str, &str)) -> Error {
+    let (component_from, port_from, component_to, port_to) = coupling;
+    Error::DuplicateCoupling {
+        component_from: component_from.to_string(),
+        port_from: port_from.to_string(),
+        component_to: component_to.to_string(),
+        port_to: port_to.to_string(),
+    }
+}
 
 /// Coupled DEVS model.
-pub struct Coupled {
+/// `T` is the type used for representing simulation times. By default, it is [`f64`].
+///
+/// When a port receives messages through several couplings, messages are propagated in the order
+/// in which the couplings were added to the model. This order does not depend on whether
+/// the `par_couplings` feature is activated, so simulation results are reproducible.
+///
+/// By default, coupled models follow the Parallel DEVS formalism: all the imminent components
+/// execute their output and transition functions at once, and components that are imminent
+/// and receive messages execute their confluent transition function.
+/// Coupled models can follow the Classic DEVS formalism instead (see [`Coupled::set_select`]).
+pub struct Coupled<T: Time = f64> {
     /// Component wrapped by the coupled model.
-    pub(crate) component: Component,
+    pub(crate) component: Component<T>,
     /// Components map. Keys are components' IDs.
     comps_map: HashMap<String, usize>,
     /// External input couplings map.
@@ -20,20 +187,45 @@ pub struct Coupled {
     /// External output couplings map.
     eoc_map: HashMap<String, HashMap<String, usize>>,
     /// Components of the DEVS coupled model (serialized for better performance).
-    pub(crate) components: Vec<Box<dyn Simulator>>,
+    pub(crate) components: Vec<Box<dyn Simulator<T>>>,
     /// External input couplings (serialized for better performance).
     pub(crate) eics: Vec<Coupling>,
     /// Internal couplings (serialized for better performance).
     pub(crate) ics: Vec<Coupling>,
     /// External output couplings (serialized for better performance).
     pub(crate) eocs: Vec<Coupling>,
+    /// Index of the receiving component of each EIC.
+    pub(crate) eic_dests: Vec<usize>,
+    /// Index of the receiving component of each IC.
+    pub(crate) ic_dests: Vec<usize>,
+    /// Indices of the ICs that leave from each component.
+    pub(crate) comp_ics: Vec<Vec<usize>>,
+    /// Indices of the EOCs that leave from each component.
+    pub(crate) comp_eocs: Vec<Vec<usize>>,
+    /// Event scheduler of the components of the coupled model.
+    pub(crate) scheduler: Scheduler<T>,
+    /// Auxiliary buffer for sorting the couplings to be propagated.
+    #[cfg(not(feature = "par_couplings"))]
+    pub(crate) coups_buf: Vec<usize>,
+    /// EICs grouped by receiving port. Each group contains the index of the receiving component.
     #[cfg(feature = "par_couplings")]
-    pub(crate) par_eics: Vec<Vec<Coupling>>,
+    pub(crate) par_eics: Vec<(usize, Vec<Coupling>)>,
+    /// EOCs and ICs grouped by receiving port. Each group contains the index of the receiving
+    /// component (EOCs do not have a receiving component).
     #[cfg(feature = "par_couplings")]
-    pub(crate) par_xxcs: Vec<Vec<Coupling>>,
+    pub(crate) par_xxcs: Vec<(Option<usize>, Vec<Coupling>)>,
+    /// Structural changes requested through [`StructureHandle`]s (if any handle was created).
+    changes: Option<ChangeQueue<T>>,
+    /// Errors of the invalid structural changes of the model and its nested coupled models
+    /// that have not been reported yet.
+    pub(crate) errors: Vec<Error>,
+    /// Tie-breaking function for imminent components (only in Classic DEVS mode).
+    select: Option<Select>,
+    /// Index of the imminent component selected in the current simulation step (if any).
+    pub(crate) selected: Option<usize>,
 }
 
-impl Coupled {
+impl<T: Time> Coupled<T> {
     /// Creates a new coupled DEVS model with the provided name.
     pub fn new(name: &str) -> Self {
         Self {
@@ -46,10 +238,21 @@ impl Coupled {
             eics: Vec::new(),
             ics: Vec::new(),
             eocs: Vec::new(),
+            eic_dests: Vec::new(),
+            ic_dests: Vec::new(),
+            comp_ics: Vec::new(),
+            comp_eocs: Vec::new(),
+            scheduler: Scheduler::default(),
+            #[cfg(not(feature = "par_couplings"))]
+            coups_buf: Vec::new(),
             #[cfg(feature = "par_couplings")]
             par_eics: Vec::new(),
             #[cfg(feature = "par_couplings")]
             par_xxcs: Vec::new(),
+            changes: None,
+            errors: Vec::new(),
+            select: None,
+            selected: None,
         }
     }
 
@@ -77,38 +280,356 @@ impl Coupled {
         self.eoc_map.values().map(|eocs| eocs.len()).sum()
     }
 
-    /// Adds a new input port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `V` and returns a reference to it.
     /// It panics if there is already an input port with the same name.
     #[inline]
-    pub fn add_in_port<T: DynRef + Clone>(&mut self, name: &str) -> InPort<T> {
-        self.component.add_in_port::<T>(name)
+    pub fn add_in_port<V: DynRef + Clone>(&mut self, name: &str) -> InPort<V> {
+        self.component.add_in_port::<V>(name)
     }
 
-    /// Adds a new output port of type `T` and returns a reference to it.
+    /// Adds a new input port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an input port with the same name.
+    #[inline]
+    pub fn try_add_in_port<V: DynRef + Clone>(&mut self, name: &str) -> Result<InPort<V>> {
+        self.component.try_add_in_port::<V>(name)
+    }
+
+    /// Adds a new output port of type `V` and returns a reference to it.
     /// It panics if there is already an output port with the same name.
     #[inline]
-    pub fn add_out_port<T: DynRef + Clone>(&mut self, name: &str) -> OutPort<T> {
-        self.component.add_out_port::<T>(name)
+    pub fn add_out_port<V: DynRef + Clone>(&mut self, name: &str) -> OutPort<V> {
+        self.component.add_out_port::<V>(name)
+    }
+
+    /// Adds a new output port of type `V` and returns a reference to it.
+    /// It returns an error if there is already an output port with the same name.
+    #[inline]
+    pub fn try_add_out_port<V: DynRef + Clone>(&mut self, name: &str) -> Result<OutPort<V>> {
+        self.component.try_add_out_port::<V>(name)
     }
 
     /// Adds a new component to the coupled model.
     /// If there is already a component with the same name as the new component, it panics.
-    pub fn add_component<T: Simulator>(&mut self, component: Box<T>) {
-        let component_name = component.get_name();
-        if self.comps_map.contains_key(component_name) {
-            panic!("coupled model already contains component with the name provided")
+    pub fn add_component<M: Simulator<T>>(&mut self, component: Box<M>) {
+        self.add_dyn_component(component);
+    }
+
+    /// Adds a new component to the coupled model.
+    /// If there is already a component with the same name as the new component, it returns an error.
+    pub fn try_add_component<M: Simulator<T>>(&mut self, component: Box<M>) -> Result<()> {
+        self.try_add_dyn_component(component)
+    }
+
+    /// Adds a new boxed component to the coupled model.
+    /// If there is already a component with the same name as the new component, it panics.
+    fn add_dyn_component(&mut self, component: Box<dyn Simulator<T>>) {
+        self.try_add_dyn_component(component)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new boxed component to the coupled model.
+    /// If there is already a component with the same name as the new component, it returns an error.
+    pub(crate) fn try_add_dyn_component(&mut self, component: Box<dyn Simulator<T>>) -> Result<()> {
+        if self.comps_map.contains_key(component.get_name()) {
+            return Err(Error::DuplicateComponent {
+                coupled: self.component.get_name().to_string(),
+                component: component.get_name().to_string(),
+            });
         }
+        self.push_component(component);
+        Ok(())
+    }
+
+    /// Removes a component from the coupled model, together with all its couplings,
+    /// and returns it. This method panics if the component does not exist.
+    ///
+    /// Note that the next simulation time of the coupled model is not updated.
+    /// To modify the structure of the model during a simulation, use a [`StructureHandle`].
+    pub fn remove_component(&mut self, name: &str) -> Box<dyn Simulator<T>> {
+        self.try_remove_component(name)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Removes a component from the coupled model, together with all its couplings,
+    /// and returns it. If the component does not exist, it returns an error.
+    pub fn try_remove_component(&mut self, name: &str) -> Result<Box<dyn Simulator<T>>> {
+        let index = self.find_component_index(name)?;
+        let component = self.components.remove(index);
+        let is_linked = |c: &Coupling| {
+            c.component_from.as_deref() == Some(name) || c.component_to.as_deref() == Some(name)
+        };
+        self.eics.retain(|c| !is_linked(c));
+        self.ics.retain(|c| !is_linked(c));
+        self.eocs.retain(|c| !is_linked(c));
+        self.reindex();
+        Ok(component)
+    }
+
+    /// Adds a new component to the coupled model without checking its name.
+    fn push_component(&mut self, component: Box<dyn Simulator<T>>) {
+        let component_name = component.get_name();
         self.comps_map
             .insert(component_name.to_string(), self.components.len());
         self.components.push(component);
+        self.comp_ics.push(Vec::new());
+        self.comp_eocs.push(Vec::new());
     }
 
-    /// Returns a reference to a component with the provided name.
+    /// Switches the coupled model to Classic DEVS mode with a tie-breaking `select` function.
+    ///
+    /// When several components are imminent, only one of them executes its output and
+    /// internal transition functions in each simulation step. The `select` function receives
+    /// the names of the imminent components (in the order in which they were added to the model)
+    /// and returns the position of the selected one. The remaining imminent components are
+    /// visited in subsequent simulation steps at the same simulation time. Components that
+    /// receive messages execute their external transition function, even if they are imminent.
+    /// Only the selected component executes its confluent transition function if it also
+    /// receives messages (e.g., from itself).
+    ///
+    /// The `select` function must return a position lower than the number of names.
+    /// It only applies to the components of this coupled model, not to nested coupled models.
+    /// When the model is flattened, the `select` function receives the new names of the components.
+    ///
+    /// ```
+    /// use xdevs::modeling::Coupled;
+    ///
+    /// let mut coupled = Coupled::<f64>::new("coupled");
+    /// // imminent components are sorted by priority. Unknown components go last
+    /// let priority = ["processor", "generator"];
+    /// coupled.set_select(move |names| {
+    ///     let rank = |name| priority.iter().position(|p| p == name).unwrap_or(priority.len());
+    ///     (0..names.len()).min_by_key(|&i| rank(&names[i])).unwrap()
+    /// });
+    /// ```
+    pub fn set_select(&mut self, select: impl Fn(&[&str]) -> usize + Send + Sync + 'static) {
+        self.select = Some(Box::new(select));
+    }
+
+    /// Switches the coupled model back to Parallel DEVS mode.
+    pub fn clear_select(&mut self) {
+        self.select = None;
+    }
+
+    /// Returns `true` if the coupled model follows the Classic DEVS formalism.
+    #[inline]
+    pub fn is_classic(&self) -> bool {
+        self.select.is_some()
+    }
+
+    /// Returns the index of a component with the provided name.
     /// If the coupled model does not contain any model with that name, it returns [`None`].
     #[inline]
-    fn get_component(&self, name: &str) -> Option<&Component> {
-        let index = *self.comps_map.get(name)?;
-        Some(self.components.get(index)?.get_component())
+    fn get_component_index(&self, name: &str) -> Option<usize> {
+        self.comps_map.get(name).copied()
+    }
+
+    /// Returns the index of a component with the provided name.
+    /// If the coupled model does not contain any model with that name, it returns an error.
+    #[inline]
+    fn find_component_index(&self, name: &str) -> Result<usize> {
+        self.get_component_index(name)
+            .ok_or_else(|| Error::ComponentNotFound {
+                coupled: self.component.get_name().to_string(),
+                component: name.to_string(),
+            })
+    }
+
+    /// Returns a reference to the component with the provided index.
+    #[inline]
+    fn get_subcomponent(&self, index: usize) -> &Component<T> {
+        self.components[index].get_component()
+    }
+
+    /// Returns the minimum next simulation time of the components.
+    #[inline]
+    pub(crate) fn next_time(&mut self) -> T {
+        let components = &self.components;
+        self.scheduler
+            .next_time(|i| components[i].get_component().get_t_next())
+    }
+
+    /// Marks as active all the components with a next simulation time less than or equal to `t`.
+    #[inline]
+    pub(crate) fn activate_imminent(&mut self, t: T) {
+        let components = &self.components;
+        self.scheduler
+            .activate_imminent(t, |i| components[i].get_component().get_t_next());
+    }
+
+    /// Marks as active only the imminent component chosen by the tie-breaking function.
+    /// The remaining imminent components are scheduled again.
+    /// It is used in Classic DEVS mode.
+    pub(crate) fn activate_selected(&mut self, t: T) {
+        self.activate_imminent(t);
+        let mut imminent = self.scheduler.active().to_vec();
+        self.scheduler.clear_active();
+        if imminent.is_empty() {
+            return;
+        }
+        imminent.sort_unstable();
+        let names: Vec<&str> = imminent
+            .iter()
+            .map(|&i| self.components[i].get_name())
+            .collect();
+        let select = self
+            .select
+            .as_ref()
+            .expect("coupled model is not in Classic DEVS mode");
+        let k = select(&names);
+        assert!(
+            k < imminent.len(),
+            "select function of {} returned {k}, but there are {} imminent components",
+            self.component.get_name(),
+            imminent.len(),
+        );
+        for (j, &i) in imminent.iter().enumerate() {
+            if j != k {
+                let t_next = self.get_subcomponent(i).get_t_next();
+                self.scheduler.schedule(i, t_next);
+            }
+        }
+        self.scheduler.activate(imminent[k]);
+        self.selected = Some(imminent[k]);
+    }
+
+    /// Schedules all the components again, removing outdated entries from the scheduler.
+    pub(crate) fn reset_scheduler(&mut self) {
+        self.scheduler.reset(self.components.len());
+        for (i, c) in self.components.iter().enumerate() {
+            self.scheduler.schedule(i, c.get_component().get_t_next());
+        }
+    }
+
+    /// Returns a handle for requesting structural changes to the coupled model during the simulation.
+    /// All the handles of a coupled model share the same queue of changes.
+    pub fn structure_handle(&mut self) -> StructureHandle<T> {
+        let changes = self
+            .changes
+            .get_or_insert_with(|| Arc::new(Mutex::new(Vec::new())));
+        StructureHandle(changes.clone())
+    }
+
+    /// Applies all the structural changes requested through [`StructureHandle`]s at time `t`.
+    /// New components inherit the simulation observer and the master seed of the coupled model,
+    /// and they are started at `t`.
+    /// Removed components are stopped at `t`.
+    /// Invalid changes are discarded, and their errors are stored until they are reported.
+    pub(crate) fn apply_changes(&mut self, t: T) {
+        let changes = match &self.changes {
+            Some(changes) => std::mem::take(&mut *changes.lock().unwrap()),
+            None => return,
+        };
+        if changes.is_empty() {
+            return;
+        }
+        for change in changes {
+            if let Err(e) = self.apply_change(change, t) {
+                self.errors.push(e);
+            }
+        }
+        self.reindex();
+    }
+
+    /// Applies a structural change at time `t`. If the change is not valid,
+    /// it returns an error and the coupled model is not modified.
+    fn apply_change(&mut self, change: StructuralChange<T>, t: T) -> Result<()> {
+        match change {
+            StructuralChange::AddComponent(mut component) => {
+                if self.comps_map.contains_key(component.get_name()) {
+                    return Err(Error::DuplicateComponent {
+                        coupled: self.component.get_name().to_string(),
+                        component: component.get_name().to_string(),
+                    });
+                }
+                if let Some((path, observer)) = self.component.clone_observer() {
+                    let path = format!("{path}.{}", component.get_name());
+                    visit_mut(&mut *component, &path, &mut |path, model| {
+                        let observer = Some(observer.clone());
+                        model.get_component_mut().set_observer(path, observer)
+                    });
+                }
+                #[cfg(feature = "rand")]
+                if let Some(stream) = self.component.get_rng_stream() {
+                    let (seed, path) = (stream.seed, &stream.path);
+                    let path = format!("{path}.{}", component.get_name());
+                    visit_mut(&mut *component, &path, &mut |path, model| {
+                        let stream = Some(crate::rng::RngStream::new(seed, path));
+                        model.get_component_mut().set_rng_stream(stream)
+                    });
+                }
+                component.start(t);
+                self.push_component(component);
+            }
+            StructuralChange::RemoveComponent(name) => self.try_remove_component(&name)?.stop(t),
+            StructuralChange::AddEic(p_from, c_to, p_to) => {
+                self.try_add_eic(&p_from, &c_to, &p_to)?
+            }
+            StructuralChange::RemoveEic(p_from, c_to, p_to) => {
+                self.try_remove_eic(&p_from, &c_to, &p_to)?
+            }
+            StructuralChange::AddIc(c_from, p_from, c_to, p_to) => {
+                self.try_add_ic(&c_from, &p_from, &c_to, &p_to)?
+            }
+            StructuralChange::RemoveIc(c_from, p_from, c_to, p_to) => {
+                self.try_remove_ic(&c_from, &p_from, &c_to, &p_to)?
+            }
+            StructuralChange::AddEoc(c_from, p_from, p_to) => {
+                self.try_add_eoc(&c_from, &p_from, &p_to)?
+            }
+            StructuralChange::RemoveEoc(c_from, p_from, p_to) => {
+                self.try_remove_eoc(&c_from, &p_from, &p_to)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds all the indices of components and couplings after a structural change.
+    /// Then, it schedules all the components again and rebuilds the caches of parallel couplings.
+    fn reindex(&mut self) {
+        self.comps_map.clear();
+        for (i, component) in self.components.iter().enumerate() {
+            self.comps_map.insert(component.get_name().to_string(), i);
+        }
+        let n = self.components.len();
+        self.comp_ics = vec![Vec::new(); n];
+        self.comp_eocs = vec![Vec::new(); n];
+
+        self.eic_map.clear();
+        self.eic_dests.clear();
+        for (j, coupling) in self.eics.iter().enumerate() {
+            let component_to = coupling.component_to.as_deref().unwrap();
+            let destination_key = component_to.to_string() + "-" + &coupling.port_to;
+            let coups = self.eic_map.entry(destination_key).or_default();
+            coups.insert(coupling.port_from.clone(), j);
+            self.eic_dests.push(self.comps_map[component_to]);
+        }
+        self.ic_map.clear();
+        self.ic_dests.clear();
+        for (j, coupling) in self.ics.iter().enumerate() {
+            let component_from = coupling.component_from.as_deref().unwrap();
+            let component_to = coupling.component_to.as_deref().unwrap();
+            let source_key = component_from.to_string() + "-" + &coupling.port_from;
+            let destination_key = component_to.to_string() + "-" + &coupling.port_to;
+            let coups = self.ic_map.entry(destination_key).or_default();
+            coups.insert(source_key, j);
+            self.comp_ics[self.comps_map[component_from]].push(j);
+            self.ic_dests.push(self.comps_map[component_to]);
+        }
+        self.eoc_map.clear();
+        for (j, coupling) in self.eocs.iter().enumerate() {
+            let component_from = coupling.component_from.as_deref().unwrap();
+            let source_key = component_from.to_string() + "-" + &coupling.port_from;
+            let coups = self.eoc_map.entry(coupling.port_to.clone()).or_default();
+            coups.insert(source_key, j);
+            self.comp_eocs[self.comps_map[component_from]].push(j);
+        }
+
+        self.reset_scheduler();
+        #[cfg(feature = "par_couplings")]
+        {
+            self.build_par_eics();
+            self.build_par_xxcs();
+        }
     }
 
     /// Adds a new EIC to the model.
@@ -121,27 +642,131 @@ impl Coupled {
     /// - ports are not compatible.
     /// - coupling already exists.
     pub fn add_eic(&mut self, port_from: &str, component_to: &str, port_to: &str) {
-        let p_from = self
-            .component
-            .get_in_port(port_from)
-            .expect("port_from does not exist");
-        let comp_to = self
-            .get_component(component_to)
-            .expect("component_to does not exist");
-        let p_to = comp_to
-            .get_in_port(port_to)
-            .expect("port_to does not exist");
-        if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
+        self.try_add_eic(port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EIC to the model.
+    /// It returns an error in the same situations in which [`Coupled::add_eic`] panics.
+    pub fn try_add_eic(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        self.try_add_eic_with(port_from, component_to, port_to, None)
+    }
+
+    /// Adds a new EIC to the model that transforms the propagated messages with `f`.
+    /// Messages of type `A` are mapped to messages of type `B`, and messages mapped to [`None`] are dropped.
+    /// Thus, the origin port must be of type `A` and the destination port must be of type `B`.
+    /// This method panics in the same situations as [`Coupled::add_eic`].
+    pub fn add_eic_map<A, B, F>(&mut self, port_from: &str, component_to: &str, port_to: &str, f: F)
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        self.try_add_eic_map(port_from, component_to, port_to, f)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EIC to the model that transforms the propagated messages with `f`.
+    /// It returns an error in the same situations in which [`Coupled::add_eic_map`] panics.
+    pub fn try_add_eic_map<A, B, F>(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        f: F,
+    ) -> Result<()>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        let transform = FilterMap::new(f);
+        self.try_add_eic_with(port_from, component_to, port_to, Some(transform))
+    }
+
+    /// Adds a new EIC to the model with an optional transformation of the propagated messages.
+    fn try_add_eic_with(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        transform: Option<Arc<dyn Transform>>,
+    ) -> Result<()> {
+        let p_from = self.component.find_in_port(port_from)?;
+        let i_to = self.find_component_index(component_to)?;
+        let p_to = self.get_subcomponent(i_to).find_in_port(port_to)?;
+        let component_from = self.component.get_name();
+        let coupling = (component_from, port_from, component_to, port_to);
+        check_compatible(&*p_from, &*p_to, transform.as_deref(), coupling)?;
+        let destination_key = component_to.to_string() + "-" + port_to;
+        if let Some(coups) = self.eic_map.get(&destination_key) {
+            if coups.contains_key(port_from) {
+                return Err(duplicate_coupling(coupling));
+            }
         }
+        self.insert_eic(port_from, p_from, i_to, port_to, p_to, transform);
+        Ok(())
+    }
+
+    /// Inserts a new EIC in the model without checking its validity.
+    fn insert_eic(
+        &mut self,
+        port_from: &str,
+        p_from: Arc<dyn Port>,
+        i_to: usize,
+        port_to: &str,
+        p_to: Arc<dyn Port>,
+        transform: Option<Arc<dyn Transform>>,
+    ) {
+        let component_to = self.components[i_to].get_name().to_string();
         let source_key = port_from.to_string();
-        let destination_key = component_to.to_string() + "-" + port_to;
+        let destination_key = component_to.clone() + "-" + port_to;
         let coups = self.eic_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
-        }
         coups.insert(source_key, self.eics.len());
-        self.eics.push((p_to, p_from));
+        self.eics.push(Coupling {
+            component_from: None,
+            port_from: port_from.to_string(),
+            component_to: Some(component_to),
+            port_to: port_to.to_string(),
+            p_from,
+            p_to,
+            transform,
+        });
+        self.eic_dests.push(i_to);
+    }
+
+    /// Removes an EIC from the model. This method panics if the coupling does not exist.
+    pub fn remove_eic(&mut self, port_from: &str, component_to: &str, port_to: &str) {
+        self.try_remove_eic(port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Removes an EIC from the model. If the coupling does not exist, it returns an error.
+    pub fn try_remove_eic(
+        &mut self,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        let destination_key = component_to.to_string() + "-" + port_to;
+        let j = self
+            .eic_map
+            .get(&destination_key)
+            .and_then(|coups| coups.get(port_from).copied())
+            .ok_or_else(|| Error::CouplingNotFound {
+                component_from: self.component.get_name().to_string(),
+                port_from: port_from.to_string(),
+                component_to: component_to.to_string(),
+                port_to: port_to.to_string(),
+            })?;
+        self.eics.remove(j);
+        self.reindex();
+        Ok(())
     }
 
     /// Adds a new IC to the model.
@@ -161,29 +786,159 @@ impl Coupled {
         component_to: &str,
         port_to: &str,
     ) {
-        let comp_from = self
-            .get_component(component_from)
-            .expect("component_from does not exist");
-        let p_from = comp_from
-            .get_out_port(port_from)
-            .expect("port_from does not exist");
-        let comp_to = self
-            .get_component(component_to)
-            .expect("component_to does not exist");
-        let p_to = comp_to
-            .get_in_port(port_to)
-            .expect("port_to does not exist");
-        if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
-        }
+        self.try_add_ic(component_from, port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new IC to the model.
+    /// It returns an error in the same situations in which [`Coupled::add_ic`] panics.
+    pub fn try_add_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        self.try_add_ic_with(component_from, port_from, component_to, port_to, None)
+    }
+
+    /// Adds a new IC to the model that transforms the propagated messages with `f`.
+    /// Messages of type `A` are mapped to messages of type `B`, and messages mapped to [`None`] are dropped.
+    /// Thus, the origin port must be of type `A` and the destination port must be of type `B`.
+    /// This method panics in the same situations as [`Coupled::add_ic`].
+    pub fn add_ic_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        f: F,
+    ) where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        self.try_add_ic_map(component_from, port_from, component_to, port_to, f)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new IC to the model that transforms the propagated messages with `f`.
+    /// It returns an error in the same situations in which [`Coupled::add_ic_map`] panics.
+    pub fn try_add_ic_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        f: F,
+    ) -> Result<()>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        let transform = FilterMap::new(f);
+        self.try_add_ic_with(
+            component_from,
+            port_from,
+            component_to,
+            port_to,
+            Some(transform),
+        )
+    }
+
+    /// Adds a new IC to the model with an optional transformation of the propagated messages.
+    fn try_add_ic_with(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+        transform: Option<Arc<dyn Transform>>,
+    ) -> Result<()> {
+        let i_from = self.find_component_index(component_from)?;
+        let p_from = self.get_subcomponent(i_from).find_out_port(port_from)?;
+        let i_to = self.find_component_index(component_to)?;
+        let p_to = self.get_subcomponent(i_to).find_in_port(port_to)?;
+        let coupling = (component_from, port_from, component_to, port_to);
+        check_compatible(&*p_from, &*p_to, transform.as_deref(), coupling)?;
         let source_key = component_from.to_string() + "-" + port_from;
         let destination_key = component_to.to_string() + "-" + port_to;
-        let coups = self.ic_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
+        if let Some(coups) = self.ic_map.get(&destination_key) {
+            if coups.contains_key(&source_key) {
+                return Err(duplicate_coupling(coupling));
+            }
         }
+        self.insert_ic(i_from, port_from, p_from, i_to, port_to, p_to, transform);
+        Ok(())
+    }
+
+    /// Inserts a new IC in the model without checking its validity.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_ic(
+        &mut self,
+        i_from: usize,
+        port_from: &str,
+        p_from: Arc<dyn Port>,
+        i_to: usize,
+        port_to: &str,
+        p_to: Arc<dyn Port>,
+        transform: Option<Arc<dyn Transform>>,
+    ) {
+        let component_from = self.components[i_from].get_name().to_string();
+        let component_to = self.components[i_to].get_name().to_string();
+        let source_key = component_from.clone() + "-" + port_from;
+        let destination_key = component_to.clone() + "-" + port_to;
+        let coups = self.ic_map.entry(destination_key).or_default();
         coups.insert(source_key, self.ics.len());
-        self.ics.push((p_to, p_from));
+        self.comp_ics[i_from].push(self.ics.len());
+        self.ics.push(Coupling {
+            component_from: Some(component_from),
+            port_from: port_from.to_string(),
+            component_to: Some(component_to),
+            port_to: port_to.to_string(),
+            p_from,
+            p_to,
+            transform,
+        });
+        self.ic_dests.push(i_to);
+    }
+
+    /// Removes an IC from the model. This method panics if the coupling does not exist.
+    pub fn remove_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) {
+        self.try_remove_ic(component_from, port_from, component_to, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Removes an IC from the model. If the coupling does not exist, it returns an error.
+    pub fn try_remove_ic(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        let source_key = component_from.to_string() + "-" + port_from;
+        let destination_key = component_to.to_string() + "-" + port_to;
+        let j = self
+            .ic_map
+            .get(&destination_key)
+            .and_then(|coups| coups.get(&source_key).copied())
+            .ok_or_else(|| Error::CouplingNotFound {
+                component_from: component_from.to_string(),
+                port_from: port_from.to_string(),
+                component_to: component_to.to_string(),
+                port_to: port_to.to_string(),
+            })?;
+        self.ics.remove(j);
+        self.reindex();
+        Ok(())
     }
 
     /// Adds a new EOC to the model.
@@ -196,62 +951,1068 @@ impl Coupled {
     /// - ports are not compatible.
     /// - coupling already exists.
     pub fn add_eoc(&mut self, component_from: &str, port_from: &str, port_to: &str) {
-        let comp_from = self
-            .get_component(component_from)
-            .expect("component_from does not exist");
-        let p_from = comp_from
-            .get_out_port(port_from)
-            .expect("port_from does not exist");
-        let p_to = self
-            .component
-            .get_out_port(port_to)
-            .expect("port_to does not exist");
-        if !p_from.is_compatible(&*p_to) {
-            panic!("ports are not compatible")
-        }
+        self.try_add_eoc(component_from, port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EOC to the model.
+    /// It returns an error in the same situations in which [`Coupled::add_eoc`] panics.
+    pub fn try_add_eoc(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        self.try_add_eoc_with(component_from, port_from, port_to, None)
+    }
+
+    /// Adds a new EOC to the model that transforms the propagated messages with `f`.
+    /// Messages of type `A` are mapped to messages of type `B`, and messages mapped to [`None`] are dropped.
+    /// Thus, the origin port must be of type `A` and the destination port must be of type `B`.
+    /// This method panics in the same situations as [`Coupled::add_eoc`].
+    pub fn add_eoc_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+        f: F,
+    ) where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        self.try_add_eoc_map(component_from, port_from, port_to, f)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EOC to the model that transforms the propagated messages with `f`.
+    /// It returns an error in the same situations in which [`Coupled::add_eoc_map`] panics.
+    pub fn try_add_eoc_map<A, B, F>(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+        f: F,
+    ) -> Result<()>
+    where
+        A: DynRef + Clone,
+        B: DynRef + Clone,
+        F: Fn(&A) -> Option<B> + DynRef,
+    {
+        let transform = FilterMap::new(f);
+        self.try_add_eoc_with(component_from, port_from, port_to, Some(transform))
+    }
+
+    /// Adds a new EOC to the model with an optional transformation of the propagated messages.
+    fn try_add_eoc_with(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+        transform: Option<Arc<dyn Transform>>,
+    ) -> Result<()> {
+        let i_from = self.find_component_index(component_from)?;
+        let p_from = self.get_subcomponent(i_from).find_out_port(port_from)?;
+        let p_to = self.component.find_out_port(port_to)?;
+        let component_to = self.component.get_name();
+        let coupling = (component_from, port_from, component_to, port_to);
+        check_compatible(&*p_from, &*p_to, transform.as_deref(), coupling)?;
         let source_key = component_from.to_string() + "-" + port_from;
+        if let Some(coups) = self.eoc_map.get(port_to) {
+            if coups.contains_key(&source_key) {
+                return Err(duplicate_coupling(coupling));
+            }
+        }
+        self.insert_eoc(i_from, port_from, p_from, port_to, p_to, transform);
+        Ok(())
+    }
+
+    /// Inserts a new EOC in the model without checking its validity.
+    fn insert_eoc(
+        &mut self,
+        i_from: usize,
+        port_from: &str,
+        p_from: Arc<dyn Port>,
+        port_to: &str,
+        p_to: Arc<dyn Port>,
+        transform: Option<Arc<dyn Transform>>,
+    ) {
+        let component_from = self.components[i_from].get_name().to_string();
+        let source_key = component_from.clone() + "-" + port_from;
         let destination_key = port_to.to_string();
         let coups = self.eoc_map.entry(destination_key).or_default();
-        if coups.contains_key(&source_key) {
-            panic!("coupling already exists");
-        }
         coups.insert(source_key, self.eocs.len());
-        self.eocs.push((p_to, p_from));
+        self.comp_eocs[i_from].push(self.eocs.len());
+        self.eocs.push(Coupling {
+            component_from: Some(component_from),
+            port_from: port_from.to_string(),
+            component_to: None,
+            port_to: port_to.to_string(),
+            p_from,
+            p_to,
+            transform,
+        });
+    }
+
+    /// Removes an EOC from the model. This method panics if the coupling does not exist.
+    pub fn remove_eoc(&mut self, component_from: &str, port_from: &str, port_to: &str) {
+        self.try_remove_eoc(component_from, port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Removes an EOC from the model. If the coupling does not exist, it returns an error.
+    pub fn try_remove_eoc(
+        &mut self,
+        component_from: &str,
+        port_from: &str,
+        port_to: &str,
+    ) -> Result<()> {
+        let source_key = component_from.to_string() + "-" + port_from;
+        let j = self
+            .eoc_map
+            .get(port_to)
+            .and_then(|coups| coups.get(&source_key).copied())
+            .ok_or_else(|| Error::CouplingNotFound {
+                component_from: component_from.to_string(),
+                port_from: port_from.to_string(),
+                component_to: self.component.get_name().to_string(),
+                port_to: port_to.to_string(),
+            })?;
+        self.eocs.remove(j);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Returns an error for a port that does not belong to the coupled model nor its components.
+    fn port_not_in_model(&self) -> Error {
+        Error::PortNotInModel {
+            coupled: self.component.get_name().to_string(),
+        }
     }
 
+    /// Returns the names of the component and input port with the given address.
+    /// If no component of the coupled model has an input port with this address, it returns an error.
+    fn find_sub_in_port(&self, address: *const ()) -> Result<(String, String)> {
+        self.components
+            .iter()
+            .find_map(|c| {
+                let port = c.get_component().get_in_port_name(address)?;
+                Some((c.get_name().to_string(), port.to_string()))
+            })
+            .ok_or_else(|| self.port_not_in_model())
+    }
+
+    /// Returns the names of the component and output port with the given address.
+    /// If no component of the coupled model has an output port with this address, it returns an error.
+    fn find_sub_out_port(&self, address: *const ()) -> Result<(String, String)> {
+        self.components
+            .iter()
+            .find_map(|c| {
+                let port = c.get_component().get_out_port_name(address)?;
+                Some((c.get_name().to_string(), port.to_string()))
+            })
+            .ok_or_else(|| self.port_not_in_model())
+    }
+
+    /// Adds a new EIC to the model from the typed identifiers of its ports.
+    /// You must provide an input port of the coupled model and an input port of one of its components.
+    /// Message types are checked at compile time. This method panics if:
+    /// - the origin port does not belong to the coupled model.
+    /// - the destination port does not belong to any component of the coupled model.
+    /// - coupling already exists.
+    pub fn add_typed_eic<V>(&mut self, port_from: &InPortId<V>, port_to: &InPortId<V>) {
+        self.try_add_typed_eic(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EIC to the model from the typed identifiers of its ports.
+    /// It returns an error in the same situations in which [`Coupled::add_typed_eic`] panics.
+    pub fn try_add_typed_eic<V>(
+        &mut self,
+        port_from: &InPortId<V>,
+        port_to: &InPortId<V>,
+    ) -> Result<()> {
+        let port_from = self
+            .component
+            .get_in_port_name(port_from.address())
+            .ok_or_else(|| self.port_not_in_model())?
+            .to_string();
+        let (component_to, port_to) = self.find_sub_in_port(port_to.address())?;
+        self.try_add_eic(&port_from, &component_to, &port_to)
+    }
+
+    /// Adds a new IC to the model from the typed identifiers of its ports.
+    /// You must provide an output port and an input port of components of the coupled model.
+    /// Message types are checked at compile time. This method panics if:
+    /// - any port does not belong to any component of the coupled model.
+    /// - coupling already exists.
+    pub fn add_typed_ic<V>(&mut self, port_from: &OutPortId<V>, port_to: &InPortId<V>) {
+        self.try_add_typed_ic(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new IC to the model from the typed identifiers of its ports.
+    /// It returns an error in the same situations in which [`Coupled::add_typed_ic`] panics.
+    pub fn try_add_typed_ic<V>(
+        &mut self,
+        port_from: &OutPortId<V>,
+        port_to: &InPortId<V>,
+    ) -> Result<()> {
+        let (component_from, port_from) = self.find_sub_out_port(port_from.address())?;
+        let (component_to, port_to) = self.find_sub_in_port(port_to.address())?;
+        self.try_add_ic(&component_from, &port_from, &component_to, &port_to)
+    }
+
+    /// Adds a new EOC to the model from the typed identifiers of its ports.
+    /// You must provide an output port of one of its components and an output port of the coupled model.
+    /// Message types are checked at compile time. This method panics if:
+    /// - the origin port does not belong to any component of the coupled model.
+    /// - the destination port does not belong to the coupled model.
+    /// - coupling already exists.
+    pub fn add_typed_eoc<V>(&mut self, port_from: &OutPortId<V>, port_to: &OutPortId<V>) {
+        self.try_add_typed_eoc(port_from, port_to)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Adds a new EOC to the model from the typed identifiers of its ports.
+    /// It returns an error in the same situations in which [`Coupled::add_typed_eoc`] panics.
+    pub fn try_add_typed_eoc<V>(
+        &mut self,
+        port_from: &OutPortId<V>,
+        port_to: &OutPortId<V>,
+    ) -> Result<()> {
+        let (component_from, port_from) = self.find_sub_out_port(port_from.address())?;
+        let port_to = self
+            .component
+            .get_out_port_name(port_to.address())
+            .ok_or_else(|| self.port_not_in_model())?
+            .to_string();
+        self.try_add_eoc(&component_from, &port_from, &port_to)
+    }
+
+    /// Collapses the hierarchy of the coupled model into a new coupled model that
+    /// only contains atomic models and direct atomic-to-atomic couplings.
+    /// Chains of EICs, ICs, and EOCs are resolved once, so messages are no longer
+    /// forwarded through every intermediate coupled model during the simulation.
+    ///
+    /// The resulting coupled model keeps the name and ports of the original model.
+    /// Atomic models are renamed after their path relative to the original model
+    /// (e.g., `subcoupled.atomic`), so their names are unique.
+    /// Transformations of chains of couplings are combined in the resulting couplings.
+    /// Resulting couplings are sorted so that ports receive messages in the same order as in
+    /// the original model, with one exception: messages that come from the input ports of the
+    /// original model are always received after messages that come from atomic models.
+    /// Structural changes requested to nested coupled models are discarded.
+    /// The tie-breaking function of the original model is kept (see [`Coupled::set_select`]),
+    /// but those of nested coupled models are discarded.
+    pub fn flatten(mut self) -> Self {
+        let mut atomics = Vec::new();
+        let mut links = HashMap::new();
+        self.drain_into("", &mut atomics, &mut links);
+
+        let mut flat = Self::new("");
+        flat.component = self.component;
+        flat.changes = self.changes;
+        flat.select = self.select;
+        for atomic in atomics {
+            flat.push_component(atomic);
+        }
+        // We index the input ports of the atomic models and the output ports of the coupled model
+        let mut in_ports = HashMap::new();
+        for (i, c) in flat.components.iter().enumerate() {
+            for (port_name, port) in c.get_component().get_in_ports() {
+                in_ports.insert(port_address(&port), (i, port_name, port));
+            }
+        }
+        let mut out_ports = HashMap::new();
+        for (port_name, port) in flat.component.get_out_ports() {
+            out_ports.insert(port_address(&port), (port_name, port));
+        }
+        // Then, we resolve the chains of couplings that start in the coupled model's input ports
+        let mut eics = Vec::new();
+        for (port_from, p_from) in flat.component.get_in_ports() {
+            for (address, transform, hops) in resolve_links(&p_from, &links) {
+                if let Some((i_to, port_to, p_to)) = in_ports.get(&address) {
+                    let (p_from, p_to) = (p_from.clone(), p_to.clone());
+                    eics.push((
+                        hops,
+                        port_from.clone(),
+                        p_from,
+                        *i_to,
+                        port_to,
+                        p_to,
+                        transform,
+                    ));
+                }
+            }
+        }
+        // And the chains of couplings that start in the atomic models' output ports
+        let (mut ics, mut eocs) = (Vec::new(), Vec::new());
+        for i_from in 0..flat.components.len() {
+            for (port_from, p_from) in flat.components[i_from].get_component().get_out_ports() {
+                for (address, transform, hops) in resolve_links(&p_from, &links) {
+                    let (port_from, p_from) = (port_from.clone(), p_from.clone());
+                    if let Some((i_to, port_to, p_to)) = in_ports.get(&address) {
+                        let coupling = (i_from, port_from, p_from, *i_to, port_to, p_to.clone());
+                        ics.push((hops, coupling, transform));
+                    } else if let Some((port_to, p_to)) = out_ports.get(&address) {
+                        let coupling = (i_from, port_from, p_from, port_to, p_to.clone());
+                        eocs.push((hops, coupling, transform));
+                    }
+                }
+            }
+        }
+        // Couplings are inserted in propagation order, so ports receive messages in the same order
+        eics.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, port_from, p_from, i_to, port_to, p_to, transform) in eics {
+            flat.insert_eic(&port_from, p_from, i_to, port_to, p_to, transform);
+        }
+        ics.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, (i_from, port_from, p_from, i_to, port_to, p_to), transform) in ics {
+            flat.insert_ic(i_from, &port_from, p_from, i_to, port_to, p_to, transform);
+        }
+        eocs.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, (i_from, port_from, p_from, port_to, p_to), transform) in eocs {
+            flat.insert_eoc(i_from, &port_from, p_from, port_to, p_to, transform);
+        }
+        flat
+    }
+
+    /// Moves all the atomic models of the hierarchy to `atomics` and all the couplings to `links`.
+    /// Atomic models are renamed after their path, which must start with `prefix`.
+    fn drain_into(
+        &mut self,
+        prefix: &str,
+        atomics: &mut Vec<Box<dyn Simulator<T>>>,
+        links: &mut Links,
+    ) {
+        let eics = self.eics.iter().enumerate().map(|(j, c)| (c, (1, j)));
+        let ics = self.ics.iter().enumerate().map(|(j, c)| (c, (0, j)));
+        let eocs = self.eocs.iter().enumerate().map(|(j, c)| (c, (0, j)));
+        for (coupling, hop) in eics.chain(ics).chain(eocs) {
+            links
+                .entry(port_address(&coupling.p_from))
+                .or_default()
+                .push((coupling.p_to.clone(), coupling.transform.clone(), hop));
+        }
+        for mut component in self.components.drain(..) {
+            let path = format!("{prefix}{}", component.get_name());
+            match component.as_coupled_mut() {
+                Some(coupled) => coupled.drain_into(&format!("{path}."), atomics, links),
+                None => {
+                    component.get_component_mut().set_name(&path);
+                    atomics.push(component);
+                }
+            }
+        }
+    }
+
+    /// Groups the EICs by receiving port. Each group contains the index of the receiving component.
+    /// Couplings are sorted by insertion order, both within and across groups. Thus, receiving
+    /// ports get their messages in the same order as in sequential simulations.
     #[cfg(feature = "par_couplings")]
     #[inline]
     pub(crate) fn build_par_eics(&mut self) {
-        for coups in self.eic_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.eics[source].clone());
-            }
-            self.par_eics.push(x);
+        self.par_eics.clear();
+        for group in sorted_groups(&self.eic_map) {
+            let dest = self.eic_dests[group[0]];
+            let coups = group.iter().map(|&j| self.eics[j].clone()).collect();
+            self.par_eics.push((dest, coups));
         }
     }
 
+    /// Groups the EOCs and ICs by receiving port. IC groups contain the index of the receiving component.
+    /// Couplings are sorted by insertion order, both within and across groups. Thus, receiving
+    /// ports get their messages in the same order as in sequential simulations.
     #[cfg(feature = "par_couplings")]
     #[inline]
-    pub(crate) fn build_par_ics(&mut self) {
-        for coups in self.ic_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.ics[source].clone());
+    pub(crate) fn build_par_xxcs(&mut self) {
+        self.par_xxcs.clear();
+        for group in sorted_groups(&self.eoc_map) {
+            let coups = group.iter().map(|&j| self.eocs[j].clone()).collect();
+            self.par_xxcs.push((None, coups));
+        }
+        for group in sorted_groups(&self.ic_map) {
+            let dest = Some(self.ic_dests[group[0]]);
+            let coups = group.iter().map(|&j| self.ics[j].clone()).collect();
+            self.par_xxcs.push((dest, coups));
+        }
+    }
+}
+
+/// Returns the indices of a set of couplings grouped by receiving port.
+/// Indices are sorted within each group, and groups are sorted by their first index.
+#[cfg(feature = "par_couplings")]
+fn sorted_groups(map: &HashMap<String, HashMap<String, usize>>) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = map
+        .values()
+        .filter(|coups| !coups.is_empty())
+        .map(|coups| {
+            let mut group: Vec<usize> = coups.values().copied().collect();
+            group.sort_unstable();
+            group
+        })
+        .collect();
+    groups.sort_unstable();
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, HasComponent, InputCtx, OutputCtx};
+    use crate::simulation::{FlatCoordinator, RootCoordinator};
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
             }
-            self.par_xxcs.push(x);
         }
     }
 
-    #[cfg(feature = "par_couplings")]
-    #[inline]
-    pub(crate) fn build_par_eocs(&mut self) {
-        for coups in self.eoc_map.values() {
-            let mut x = Vec::new();
-            for &source in coups.values() {
-                x.push(self.eocs[source].clone());
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Atomic model that forwards the messages it receives in zero time.
+    struct Relay {
+        component: Component,
+        buffer: Vec<usize>,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+    }
+
+    impl Relay {
+        fn new(name: &str) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                buffer: Vec::new(),
+                input,
+                output,
             }
-            self.par_xxcs.push(x);
         }
     }
+
+    impl HasComponent for Relay {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Relay {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_values(&self.output, &self.buffer);
+        }
+
+        fn delta_int(&mut self) {
+            self.buffer.clear();
+        }
+
+        fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+            self.buffer.extend_from_slice(input.get_values(&self.input));
+        }
+
+        fn ta(&self) -> f64 {
+            match self.buffer.is_empty() {
+                true => f64::INFINITY,
+                false => 0.,
+            }
+        }
+    }
+
+    /// Returns a coupled model with an input port `input` (bool), an output port `output` (usize),
+    /// a generator, and a relay. The generator is coupled to the relay, but not to the ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_component(Box::new(Relay::new("relay")));
+        coupled.add_ic("generator", "output", "relay", "input");
+        coupled
+    }
+
+    fn mismatch(
+        from: (&str, &str),
+        to: (&str, &str),
+        expected: &'static str,
+        found: &'static str,
+    ) -> Error {
+        Error::IncompatiblePorts(Box::new(PortMismatch {
+            component_from: from.0.to_string(),
+            port_from: from.1.to_string(),
+            component_to: to.0.to_string(),
+            port_to: to.1.to_string(),
+            expected,
+            found,
+        }))
+    }
+
+    fn duplicate(from: (&str, &str), to: (&str, &str)) -> Error {
+        duplicate_coupling((from.0, from.1, to.0, to.1))
+    }
+
+    /// Returns the number of EICs, ICs, and EOCs of a coupled model.
+    fn n_couplings(coupled: &Coupled) -> (usize, usize, usize) {
+        (coupled.n_eics(), coupled.n_ics(), coupled.n_eocs())
+    }
+
+    #[test]
+    fn test_try_add_ports_and_components() {
+        let mut coupled = create_model();
+        let expected = Error::DuplicateInPort {
+            component: "root".to_string(),
+            port: "input".to_string(),
+        };
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_in_port::<bool>("input").map(|_| ())
+        );
+        // ports are identified by name, regardless of their type
+        let expected = Error::DuplicateOutPort {
+            component: "root".to_string(),
+            port: "output".to_string(),
+        };
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_out_port::<f64>("output").map(|_| ())
+        );
+        // input and output ports have different namespaces
+        coupled.try_add_out_port::<bool>("input").unwrap();
+
+        let expected = Error::DuplicateComponent {
+            coupled: "root".to_string(),
+            component: "relay".to_string(),
+        };
+        // the name is what matters, not the type of the component
+        let other = Generator::new("relay", 1.);
+        assert_eq!(Err(expected), coupled.try_add_component(Box::new(other)));
+        assert_eq!(2, coupled.n_components());
+        assert_eq!(
+            "coupled model root already contains component relay",
+            coupled
+                .try_add_component(Box::new(Relay::new("relay")))
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_try_add_eic() {
+        let mut coupled = create_model();
+        let not_found = Error::ComponentNotFound {
+            coupled: "root".to_string(),
+            component: "other".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eic("input", "other", "input")
+        );
+        let not_found = Error::InPortNotFound {
+            component: "root".to_string(),
+            port: "other".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eic("other", "generator", "input")
+        );
+        let not_found = Error::InPortNotFound {
+            component: "generator".to_string(),
+            port: "output".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eic("input", "generator", "output")
+        );
+        let expected = mismatch(("root", "input"), ("relay", "input"), "usize", "bool");
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_eic("input", "relay", "input")
+        );
+        assert_eq!((0, 1, 0), n_couplings(&coupled));
+
+        coupled.try_add_eic("input", "generator", "input").unwrap();
+        let expected = duplicate(("root", "input"), ("generator", "input"));
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_eic("input", "generator", "input")
+        );
+        // couplings are identified by their ports, regardless of their transformation
+        let expected = duplicate(("root", "input"), ("generator", "input"));
+        let err = coupled.try_add_eic_map("input", "generator", "input", |&x: &bool| Some(!x));
+        assert_eq!(Err(expected), err);
+        assert_eq!((1, 1, 0), n_couplings(&coupled));
+
+        // transformations must accept the sending port and produce the receiving port
+        let err = coupled.try_add_eic_map("input", "relay", "input", |&x: &usize| Some(x));
+        let expected = mismatch(("root", "input"), ("relay", "input"), "usize", "bool");
+        assert_eq!(Err(expected), err);
+        let err = coupled.try_add_eic_map("input", "relay", "input", |&x: &bool| Some(x));
+        let expected = mismatch(("root", "input"), ("relay", "input"), "usize", "bool");
+        assert_eq!(Err(expected), err);
+        coupled
+            .try_add_eic_map("input", "relay", "input", |&x: &bool| Some(x as usize))
+            .unwrap();
+        assert_eq!((2, 1, 0), n_couplings(&coupled));
+    }
+
+    #[test]
+    fn test_try_add_ic() {
+        let mut coupled = create_model();
+        coupled.add_component(Box::new(Generator::new("other", 1.)));
+        for (from, to) in [("missing", "generator"), ("generator", "missing")] {
+            let not_found = Error::ComponentNotFound {
+                coupled: "root".to_string(),
+                component: "missing".to_string(),
+            };
+            assert_eq!(
+                Err(not_found),
+                coupled.try_add_ic(from, "output", to, "input")
+            );
+        }
+        let not_found = Error::OutPortNotFound {
+            component: "generator".to_string(),
+            port: "input".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_ic("generator", "input", "other", "input")
+        );
+        let not_found = Error::InPortNotFound {
+            component: "other".to_string(),
+            port: "output".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_ic("generator", "output", "other", "output")
+        );
+        let err = coupled
+            .try_add_ic("generator", "output", "other", "input")
+            .unwrap_err();
+        let expected = mismatch(("generator", "output"), ("other", "input"), "bool", "usize");
+        assert_eq!(expected, err);
+        assert_eq!(
+            "port generator.output is not compatible with port other.input \
+             (expected messages of type bool, found usize)",
+            err.to_string()
+        );
+        let expected = duplicate(("generator", "output"), ("relay", "input"));
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_ic("generator", "output", "relay", "input")
+        );
+        let expected = duplicate(("generator", "output"), ("relay", "input"));
+        let err = coupled.try_add_ic_map("generator", "output", "relay", "input", |&x: &usize| {
+            Some(x + 1)
+        });
+        assert_eq!(Err(expected), err);
+        let err = coupled.try_add_ic_map("generator", "output", "other", "input", |_: &bool| {
+            Some(true)
+        });
+        let expected = mismatch(("generator", "output"), ("other", "input"), "bool", "usize");
+        assert_eq!(Err(expected), err);
+        let err = coupled.try_add_ic_map("generator", "output", "other", "input", |&x: &usize| {
+            Some(x)
+        });
+        let expected = mismatch(("generator", "output"), ("other", "input"), "bool", "usize");
+        assert_eq!(Err(expected), err);
+        assert_eq!((0, 1, 0), n_couplings(&coupled));
+
+        coupled
+            .try_add_ic_map("generator", "output", "other", "input", |_: &usize| {
+                Some(true)
+            })
+            .unwrap();
+        assert_eq!((0, 2, 0), n_couplings(&coupled));
+        let expected = Error::CouplingNotFound {
+            component_from: "relay".to_string(),
+            port_from: "output".to_string(),
+            component_to: "other".to_string(),
+            port_to: "input".to_string(),
+        };
+        assert_eq!(
+            Err(expected),
+            coupled.try_remove_ic("relay", "output", "other", "input")
+        );
+        assert!(coupled.try_remove_component("missing").is_err());
+        // removing a component also removes its couplings
+        coupled.try_remove_component("generator").unwrap();
+        assert_eq!((0, 0, 0), n_couplings(&coupled));
+    }
+
+    #[test]
+    fn test_try_add_eoc() {
+        let mut coupled = create_model();
+        let not_found = Error::ComponentNotFound {
+            coupled: "root".to_string(),
+            component: "missing".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eoc("missing", "output", "output")
+        );
+        let not_found = Error::OutPortNotFound {
+            component: "generator".to_string(),
+            port: "input".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eoc("generator", "input", "output")
+        );
+        let not_found = Error::OutPortNotFound {
+            component: "root".to_string(),
+            port: "input".to_string(),
+        };
+        assert_eq!(
+            Err(not_found),
+            coupled.try_add_eoc("generator", "output", "input")
+        );
+        coupled.add_out_port::<f64>("real");
+        let expected = mismatch(("relay", "output"), ("root", "real"), "f64", "usize");
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_eoc("relay", "output", "real")
+        );
+
+        coupled.try_add_eoc("relay", "output", "output").unwrap();
+        let expected = duplicate(("relay", "output"), ("root", "output"));
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_eoc("relay", "output", "output")
+        );
+        let expected = duplicate(("relay", "output"), ("root", "output"));
+        let err = coupled.try_add_eoc_map("relay", "output", "output", |&x: &usize| Some(x));
+        assert_eq!(Err(expected), err);
+        let err = coupled.try_add_eoc_map("relay", "output", "real", |&x: &f64| Some(x));
+        let expected = mismatch(("relay", "output"), ("root", "real"), "f64", "usize");
+        assert_eq!(Err(expected), err);
+        let err = coupled.try_add_eoc_map("relay", "output", "real", |&x: &usize| Some(x));
+        let expected = mismatch(("relay", "output"), ("root", "real"), "f64", "usize");
+        assert_eq!(Err(expected), err);
+        assert_eq!((0, 1, 1), n_couplings(&coupled));
+
+        coupled
+            .try_add_eoc_map("relay", "output", "real", |&x: &usize| Some(x as f64))
+            .unwrap();
+        assert_eq!((0, 1, 2), n_couplings(&coupled));
+        coupled.try_remove_eoc("relay", "output", "output").unwrap();
+        let expected = Error::CouplingNotFound {
+            component_from: "relay".to_string(),
+            port_from: "output".to_string(),
+            component_to: "root".to_string(),
+            port_to: "output".to_string(),
+        };
+        assert_eq!(
+            Err(expected),
+            coupled.try_remove_eoc("relay", "output", "output")
+        );
+    }
+
+    #[test]
+    fn test_typed_couplings() {
+        let generator = Generator::new("generator", 2.);
+        let (gen_in, gen_out) = (generator.input.id(), generator.output.id());
+        let relay = Relay::new("relay");
+        let (relay_in, relay_out) = (relay.input.id(), relay.output.id());
+
+        let mut coupled = Coupled::new("root");
+        let input = coupled.add_in_port::<bool>("input").id();
+        let output = coupled.add_out_port::<usize>("output").id();
+        coupled.add_component(Box::new(generator));
+        coupled.add_component(Box::new(relay));
+        coupled.add_typed_eic(&input, &gen_in);
+        coupled.add_typed_ic(&gen_out, &relay_in);
+        coupled.add_typed_eoc(&relay_out, &output);
+        assert_eq!((1, 1, 1), n_couplings(&coupled));
+        assert_eq!(
+            Some((Some("generator"), "output")),
+            coupled
+                .ics
+                .first()
+                .map(|c| (c.info().component_from, c.info().port_from))
+        );
+        let expected = duplicate(("root", "input"), ("generator", "input"));
+        assert_eq!(Err(expected), coupled.try_add_typed_eic(&input, &gen_in));
+        let expected = duplicate(("generator", "output"), ("relay", "input"));
+        assert_eq!(Err(expected), coupled.try_add_typed_ic(&gen_out, &relay_in));
+        let expected = duplicate(("relay", "output"), ("root", "output"));
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_typed_eoc(&relay_out, &output)
+        );
+
+        // ports of other models cannot be coupled
+        let mut other = Relay::new("other");
+        let other_in = other.input.id();
+        let other_out = other.output.id();
+        let sub_in = other.component.add_in_port::<bool>("flag").id();
+        let expected = Error::PortNotInModel {
+            coupled: "root".to_string(),
+        };
+        assert_eq!(
+            Err(expected.clone()),
+            coupled.try_add_typed_eic(&sub_in, &gen_in)
+        );
+        assert_eq!(
+            Err(expected.clone()),
+            coupled.try_add_typed_eic(&input, &sub_in)
+        );
+        assert_eq!(
+            Err(expected.clone()),
+            coupled.try_add_typed_ic(&gen_out, &other_in)
+        );
+        assert_eq!(
+            Err(expected.clone()),
+            coupled.try_add_typed_ic(&other_out, &relay_in)
+        );
+        assert_eq!(
+            Err(expected),
+            coupled.try_add_typed_eoc(&other_out, &output)
+        );
+        assert_eq!((1, 1, 1), n_couplings(&coupled));
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<usize>("output");
+        simulator.simulate(5.);
+        assert_eq!(
+            &[(2., 0), (4., 1)],
+            simulator.get_output::<usize>("output").unwrap()
+        );
+    }
+
+    #[test]
+    // `is_multiple_of` for unsigned integers requires Rust 1.87
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    fn test_coupling_map() {
+        // Generator -> (even messages only) -> relay -> (x10, as f64) -> sub -> (+0.5) -> root
+        let create_model = || {
+            let mut sub = Coupled::new("sub");
+            sub.add_in_port::<bool>("stop");
+            sub.add_out_port::<f64>("output");
+            sub.add_component(Box::new(Generator::new("generator", 2.)));
+            sub.add_component(Box::new(Relay::new("relay")));
+            sub.add_eic("stop", "generator", "input");
+            sub.add_ic_map("generator", "output", "relay", "input", |x: &usize| {
+                (x % 2 == 0).then_some(*x)
+            });
+            sub.add_eoc_map("relay", "output", "output", |x: &usize| {
+                Some(*x as f64 * 10.)
+            });
+            let mut coupled = Coupled::new("root");
+            coupled.add_in_port::<bool>("stop");
+            coupled.add_out_port::<f64>("output");
+            coupled.add_component(Box::new(sub));
+            coupled.add_eic("stop", "sub", "stop");
+            coupled.add_eoc_map("sub", "output", "output", |x: &f64| Some(x + 0.5));
+            coupled
+        };
+        let expected = [(2., 0.5), (6., 20.5), (10., 40.5)];
+
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.collect_output::<f64>("output");
+        simulator.simulate(11.);
+        assert_eq!(&expected, simulator.get_output::<f64>("output").unwrap());
+
+        // transformations along chains of couplings are combined when flattening the model
+        let mut simulator = FlatCoordinator::new(create_model());
+        simulator.collect_output::<f64>("output");
+        simulator.simulate(11.);
+        assert_eq!(&expected, simulator.get_output::<f64>("output").unwrap());
+    }
+
+    #[test]
+    fn test_coupling_order() {
+        // Generators send their messages at the same time through couplings to the same ports
+        let names = ["h", "g", "f", "e", "d", "c", "b", "a"];
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("stop");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_out_port::<usize>("relayed");
+        coupled.add_component(Box::new(Relay::new("relay")));
+        for (i, name) in names.into_iter().enumerate() {
+            coupled.add_component(Box::new(Generator::new(name, 1.)));
+            coupled.add_eic("stop", name, "input");
+            coupled.add_eoc_map(name, "output", "output", move |_: &usize| Some(i));
+            coupled.add_ic_map(name, "output", "relay", "input", move |_: &usize| Some(i));
+        }
+        coupled.add_eoc("relay", "output", "relayed");
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<usize>("output");
+        simulator.collect_output::<usize>("relayed");
+        simulator.simulate(1.5);
+        let expected: Vec<_> = (0..names.len()).map(|i| (1., i)).collect();
+        assert_eq!(expected, simulator.get_output::<usize>("output").unwrap());
+        assert_eq!(expected, simulator.get_output::<usize>("relayed").unwrap());
+    }
+
+    #[test]
+    fn test_flatten_order() {
+        // Ports receive messages in insertion order of the couplings, not in order of the senders
+        let create_model = || {
+            let mut inner = Coupled::new("inner");
+            inner.add_in_port::<bool>("stop");
+            inner.add_out_port::<usize>("output");
+            inner.add_component(Box::new(Generator::new("generator", 1.)));
+            inner.add_eic("stop", "generator", "input");
+            inner.add_eoc("generator", "output", "output");
+            let mut sub = Coupled::new("sub");
+            sub.add_in_port::<usize>("a");
+            sub.add_in_port::<usize>("b");
+            sub.add_out_port::<usize>("output");
+            sub.add_component(Box::new(Relay::new("relay")));
+            sub.add_eic("b", "relay", "input");
+            sub.add_eic("a", "relay", "input");
+            sub.add_eoc("relay", "output", "output");
+            let mut coupled = Coupled::new("root");
+            coupled.add_in_port::<bool>("stop");
+            coupled.add_out_port::<usize>("output");
+            coupled.add_out_port::<usize>("relayed");
+            for name in ["g0", "g1", "g2"] {
+                coupled.add_component(Box::new(Generator::new(name, 1.)));
+                coupled.add_eic("stop", name, "input");
+            }
+            coupled.add_component(Box::new(inner));
+            coupled.add_component(Box::new(sub));
+            coupled.add_eic("stop", "inner", "stop");
+            coupled.add_ic_map("g0", "output", "sub", "b", |_: &usize| Some(0usize));
+            coupled.add_ic_map("g2", "output", "sub", "a", |_: &usize| Some(2usize));
+            coupled.add_ic_map("g1", "output", "sub", "a", |_: &usize| Some(1usize));
+            coupled.add_ic_map("inner", "output", "sub", "b", |_: &usize| Some(3usize));
+            coupled.add_eoc_map("g2", "output", "output", |_: &usize| Some(2usize));
+            coupled.add_eoc_map("inner", "output", "output", |_: &usize| Some(3usize));
+            coupled.add_eoc_map("g0", "output", "output", |_: &usize| Some(0usize));
+            coupled.add_eoc("sub", "output", "relayed");
+            coupled
+        };
+        let check = |simulator: &mut RootCoordinator<Coupled>| {
+            simulator.collect_output::<usize>("output");
+            simulator.collect_output::<usize>("relayed");
+            simulator.simulate(1.5);
+            let output = simulator.get_output::<usize>("output").unwrap();
+            assert_eq!(&[(1., 2), (1., 3), (1., 0)], output);
+            let relayed = simulator.get_output::<usize>("relayed").unwrap();
+            assert_eq!(&[(1., 0), (1., 3), (1., 2), (1., 1)], relayed);
+        };
+        check(&mut RootCoordinator::new(create_model()));
+        check(&mut FlatCoordinator::new(create_model()));
+    }
+
+    #[test]
+    fn test_classic_select() {
+        // Two generators that are imminent at the same time and stop each other
+        let create_model = || {
+            let mut coupled = Coupled::new("root");
+            coupled.add_out_port::<usize>("output");
+            coupled.add_component(Box::new(Generator::new("a", 1.)));
+            coupled.add_component(Box::new(Generator::new("b", 1.)));
+            for (i, (name, other)) in [("a", "b"), ("b", "a")].into_iter().enumerate() {
+                coupled.add_eoc_map(name, "output", "output", move |_: &usize| Some(i));
+                coupled.add_ic_map(name, "output", other, "input", |_: &usize| Some(true));
+            }
+            coupled
+        };
+        let simulate = |coupled: Coupled| {
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.collect_output::<usize>("output");
+            simulator.simulate(3.5);
+            simulator.get_output::<usize>("output").unwrap().to_vec()
+        };
+
+        // In Parallel DEVS, both generators send a message and stop
+        let coupled = create_model();
+        assert!(!coupled.is_classic());
+        assert_eq!(vec![(1., 0), (1., 1)], simulate(coupled));
+
+        // In Classic DEVS, the selected generator stops the other one before it sends any message
+        let mut coupled = create_model();
+        coupled.set_select(|_| 0);
+        assert!(coupled.is_classic());
+        assert_eq!(vec![(1., 0), (2., 0), (3., 0)], simulate(coupled));
+
+        // Only imminent components are candidates: after time 1, generator a is passive
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut coupled = create_model();
+        let c = calls.clone();
+        coupled.set_select(move |names| {
+            c.lock().unwrap().push(names.join(","));
+            names.len() - 1
+        });
+        assert_eq!(vec![(1., 1), (2., 1), (3., 1)], simulate(coupled));
+        assert_eq!(vec!["a,b", "b", "b"], *calls.lock().unwrap());
+
+        // The tie-breaking function is kept when the model is flattened
+        let mut sub = create_model();
+        sub.set_select(|names| names.len() - 1);
+        let mut simulator = FlatCoordinator::new(sub);
+        simulator.collect_output::<usize>("output");
+        simulator.simulate(3.5);
+        let output = simulator.get_output::<usize>("output").unwrap();
+        assert_eq!(vec![(1., 1), (2., 1), (3., 1)], output);
+
+        // Back to Parallel DEVS
+        let mut coupled = create_model();
+        coupled.set_select(|_| 0);
+        coupled.clear_select();
+        assert_eq!(vec![(1., 0), (1., 1)], simulate(coupled));
+    }
 }
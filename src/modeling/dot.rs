@@ -0,0 +1,282 @@
+use super::port::Port;
+use super::{Coupled, Time};
+use crate::simulation::Simulator;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Returns a quoted DOT identifier.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Escapes the special characters of a field of a DOT record label.
+/// The resulting label can be directly enclosed in quotes.
+fn escape(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Returns the fields of a DOT record label for a set of ports.
+/// Fields are named after the index of the port and the given prefix (e.g., `i0`, `i1`, ...).
+fn port_fields(ports: &[(String, Arc<dyn Port>)], prefix: char) -> String {
+    let fields: Vec<_> = ports
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| format!("<{prefix}{i}> {}", escape(name)))
+        .collect();
+    fields.join("|")
+}
+
+/// Returns the index of a port in a set of ports.
+fn port_index(ports: &[(String, Arc<dyn Port>)], name: &str) -> usize {
+    ports.iter().position(|(port, _)| port == name).unwrap()
+}
+
+/// Returns the DOT endpoint of an input port of a model.
+/// Input ports of coupled models belong to a dedicated node.
+fn in_endpoint<T: Time>(model: &dyn Simulator<T>, path: &str, port: &str) -> String {
+    let ports = model.get_component().get_in_ports();
+    let node = match model.as_coupled() {
+        Some(_) => format!("{path}/in"),
+        None => path.to_string(),
+    };
+    format!("{}:i{}", quote(&node), port_index(&ports, port))
+}
+
+/// Returns the DOT endpoint of an output port of a model.
+/// Output ports of coupled models belong to a dedicated node.
+fn out_endpoint<T: Time>(model: &dyn Simulator<T>, path: &str, port: &str) -> String {
+    let ports = model.get_component().get_out_ports();
+    let node = match model.as_coupled() {
+        Some(_) => format!("{path}/out"),
+        None => path.to_string(),
+    };
+    format!("{}:o{}", quote(&node), port_index(&ports, port))
+}
+
+/// Writes a coupled model with the given full path as a DOT cluster.
+fn write_coupled<T: Time>(dot: &mut String, coupled: &Coupled<T>, path: &str, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let name = coupled.component.get_name();
+    writeln!(
+        dot,
+        "{indent}subgraph {} {{",
+        quote(&format!("cluster_{path}"))
+    )
+    .unwrap();
+    writeln!(dot, "{indent}  label={};", quote(name)).unwrap();
+    let in_ports = coupled.component.get_in_ports();
+    if !in_ports.is_empty() {
+        let (id, label) = (quote(&format!("{path}/in")), port_fields(&in_ports, 'i'));
+        writeln!(dot, "{indent}  {id} [label=\"{label}\"];").unwrap();
+    }
+    let out_ports = coupled.component.get_out_ports();
+    if !out_ports.is_empty() {
+        let (id, label) = (quote(&format!("{path}/out")), port_fields(&out_ports, 'o'));
+        writeln!(dot, "{indent}  {id} [label=\"{label}\"];").unwrap();
+    }
+    for component in coupled.components.iter() {
+        let child_path = format!("{path}.{}", component.get_name());
+        match component.as_coupled() {
+            Some(child) => write_coupled(dot, child, &child_path, depth + 1),
+            None => {
+                let c = component.get_component();
+                let label = format!(
+                    "{{{{{}}}|{}|{{{}}}}}",
+                    port_fields(&c.get_in_ports(), 'i'),
+                    escape(c.get_name()),
+                    port_fields(&c.get_out_ports(), 'o'),
+                );
+                let id = quote(&child_path);
+                writeln!(dot, "{indent}  {id} [label=\"{label}\"];").unwrap();
+            }
+        }
+    }
+    let indices: HashMap<_, _> = coupled
+        .components
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.get_name(), i))
+        .collect();
+    let child = |name: &str| -> (&dyn Simulator<T>, String) {
+        let component = &coupled.components[indices[name]];
+        (&**component, format!("{path}.{name}"))
+    };
+    for coupling in coupled.eics.iter().chain(&coupled.ics).chain(&coupled.eocs) {
+        let info = coupling.info();
+        let from = match info.component_from {
+            Some(name) => {
+                let (model, child_path) = child(name);
+                out_endpoint(model, &child_path, info.port_from)
+            }
+            None => {
+                let i = port_index(&in_ports, info.port_from);
+                format!("{}:i{i}", quote(&format!("{path}/in")))
+            }
+        };
+        let to = match info.component_to {
+            Some(name) => {
+                let (model, child_path) = child(name);
+                in_endpoint(model, &child_path, info.port_to)
+            }
+            None => {
+                let i = port_index(&out_ports, info.port_to);
+                format!("{}:o{i}", quote(&format!("{path}/out")))
+            }
+        };
+        writeln!(dot, "{indent}  {from} -> {to};").unwrap();
+    }
+    writeln!(dot, "{indent}}}").unwrap();
+}
+
+impl<T: Time> Coupled<T> {
+    /// Returns the structure of the coupled model in the DOT language of
+    /// [Graphviz](https://graphviz.org/) (e.g., to render it with `dot -Tsvg`).
+    ///
+    /// Coupled models are rendered as nested clusters, with one record node for their input ports
+    /// and another for their output ports. Atomic models are rendered as record nodes with their
+    /// input ports, name, and output ports. EICs, ICs, and EOCs are rendered as edges between ports.
+    /// Nodes are identified by the full path of their model (e.g., `root.subcoupled.atomic`).
+    pub fn to_dot(&self) -> String {
+        let name = self.component.get_name();
+        let mut dot = format!("digraph {} {{\n", quote(name));
+        dot.push_str("  rankdir=LR;\n  node [shape=record];\n");
+        write_coupled(&mut dot, self, name, 1);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modeling::{Atomic, Component, Coupled, HasComponent, InputCtx, OutputCtx};
+
+    /// Passive atomic model with the given input and output ports.
+    struct Node {
+        component: Component,
+    }
+
+    impl Node {
+        fn new(name: &str, in_ports: &[&str], out_ports: &[&str]) -> Self {
+            let mut component = Component::new(name);
+            for port in in_ports {
+                component.add_in_port::<usize>(port);
+            }
+            for port in out_ports {
+                component.add_out_port::<usize>(port);
+            }
+            Self { component }
+        }
+    }
+
+    impl HasComponent for Node {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Node {
+        fn lambda(&self, _output: &OutputCtx) {}
+
+        fn delta_int(&mut self) {}
+
+        fn delta_ext(&mut self, _e: f64, _input: &InputCtx) {}
+
+        fn ta(&self) -> f64 {
+            f64::INFINITY
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut sub = Coupled::new("sub");
+        sub.add_in_port::<usize>("input");
+        sub.add_out_port::<usize>("output");
+        sub.add_component(Box::new(Node::new("generator", &["input"], &["output"])));
+        sub.add_eic("input", "generator", "input");
+        sub.add_eoc("generator", "output", "output");
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(sub));
+        coupled.add_component(Box::new(Node::new("relay", &["input"], &["output"])));
+        coupled.add_eic("input", "sub", "input");
+        coupled.add_ic("sub", "output", "relay", "input");
+        coupled.add_eoc("relay", "output", "output");
+        let expected = r#"digraph "root" {
+  rankdir=LR;
+  node [shape=record];
+  subgraph "cluster_root" {
+    label="root";
+    "root/in" [label="<i0> input"];
+    "root/out" [label="<o0> output"];
+    subgraph "cluster_root.sub" {
+      label="sub";
+      "root.sub/in" [label="<i0> input"];
+      "root.sub/out" [label="<o0> output"];
+      "root.sub.generator" [label="{{<i0> input}|generator|{<o0> output}}"];
+      "root.sub/in":i0 -> "root.sub.generator":i0;
+      "root.sub.generator":o0 -> "root.sub/out":o0;
+    }
+    "root.relay" [label="{{<i0> input}|relay|{<o0> output}}"];
+    "root/in":i0 -> "root.sub/in":i0;
+    "root.sub/out":o0 -> "root.relay":i0;
+    "root.relay":o0 -> "root/out":o0;
+  }
+}
+"#;
+        assert_eq!(expected, coupled.to_dot());
+    }
+
+    #[test]
+    fn test_to_dot_ports() {
+        // coupled models without ports have no port nodes, and ports are indexed in order
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Node::new("a", &[], &["x", "y"])));
+        coupled.add_component(Box::new(Node::new("b", &["x", "y"], &[])));
+        coupled.add_ic("a", "y", "b", "x");
+        coupled.add_ic("a", "x", "b", "y");
+        let expected = r#"digraph "root" {
+  rankdir=LR;
+  node [shape=record];
+  subgraph "cluster_root" {
+    label="root";
+    "root.a" [label="{{}|a|{<o0> x|<o1> y}}"];
+    "root.b" [label="{{<i0> x|<i1> y}|b|{}}"];
+    "root.a":o1 -> "root.b":i0;
+    "root.a":o0 -> "root.b":i1;
+  }
+}
+"#;
+        assert_eq!(expected, coupled.to_dot());
+    }
+
+    #[test]
+    fn test_to_dot_escape() {
+        let mut coupled = Coupled::new("my \"root\"");
+        coupled.add_component(Box::new(Node::new("a|b", &["<in>"], &["{out}"])));
+        let expected = r#"digraph "my \"root\"" {
+  rankdir=LR;
+  node [shape=record];
+  subgraph "cluster_my \"root\"" {
+    label="my \"root\"";
+    "my \"root\".a|b" [label="{{<i0> \<in\>}|a\|b|{<o0> \{out\}}}"];
+  }
+}
+"#;
+        assert_eq!(expected, coupled.to_dot());
+    }
+}
@@ -1,6 +1,9 @@
+use super::{Component, Time};
 use crate::DynRef;
 use std::any::Any;
 use std::cell::UnsafeCell;
+use std::fmt::{self, Debug};
+use std::marker::PhantomData;
 use std::ops::Deref;
 use std::sync::Arc;
 
@@ -27,6 +30,13 @@ pub(crate) trait Port: DynRef {
     /// Returns `true` if other port is compatible.
     fn is_compatible(&self, other: &dyn Port) -> bool;
 
+    /// Returns the name of the type of the messages of the port.
+    fn type_name(&self) -> &'static str;
+
+    /// Creates a new empty port with the same message type.
+    #[cfg(feature = "serde")]
+    fn new_empty(&self) -> Arc<dyn Port>;
+
     /// Propagates messages from the port to other receiving port.
     ///
     /// # Safety
@@ -34,17 +44,83 @@ pub(crate) trait Port: DynRef {
     /// This method can only be executed by a [`super::Coupled`] model when propagating
     /// messages in its [`crate::simulation::Simulator`] trait implementation.
     unsafe fn propagate(&self, port_to: &dyn Port);
+
+    /// Calls `f` for every message in the port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a [`crate::simulation::Simulator`]
+    /// when notifying its [`crate::simulation::SimObserver`] about the messages in its ports.
+    unsafe fn for_each_value(&self, f: &mut dyn FnMut(&dyn Debug));
+}
+
+impl dyn Port {
+    /// Returns `true` if the port is of type `T`.
+    #[inline]
+    pub(crate) fn is_type<T: DynRef + Clone>(&self) -> bool {
+        self.as_any().is::<Bag<T>>()
+    }
+
+    /// Returns a reference to the slice of messages of the port.
+    /// It returns [`None`] if the port is not of type `T`.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by the [`crate::simulation::RootCoordinator`]
+    /// when reading messages from the output ports of the model under simulation.
+    #[inline]
+    pub(crate) unsafe fn get_values<T: DynRef + Clone>(&self) -> Option<&[T]> {
+        Some(self.as_any().downcast_ref::<Bag<T>>()?.borrow())
+    }
+
+    /// Injects new values into the port. It returns `false` if the port is not of type `T`.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by the [`crate::simulation::RootCoordinator`]
+    /// when injecting messages into the input ports of the model under simulation.
+    pub(crate) unsafe fn inject<T: DynRef + Clone>(&self, values: &[T]) -> bool {
+        match self.as_any().downcast_ref::<Bag<T>>() {
+            Some(bag) => {
+                bag.borrow_mut().extend_from_slice(values);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
+/// Formatter of the messages of a port. Only traced ports have a formatter.
+type MessageFmt<T> = fn(&T, &mut fmt::Formatter) -> fmt::Result;
+
 /// Bag of DEVS messages. Each port has its own bag.
 #[derive(Debug)]
-pub(super) struct Bag<T>(UnsafeCell<Vec<T>>);
+pub(super) struct Bag<T> {
+    /// Messages in the bag.
+    values: UnsafeCell<Vec<T>>,
+    /// Identifier of the component that owns the port (see [`super::Component::id`]).
+    /// Ports that do not belong to any component have identifier 0.
+    owner: u64,
+    /// Formatter of the messages for simulation observers (if any).
+    fmt: Option<MessageFmt<T>>,
+}
 
 impl<T> Bag<T> {
-    /// Creates a new message bag wrapped in an [`Arc`].
+    /// Creates a new message bag wrapped in an [`Arc`] for the component with the provided identifier.
+    /// Simulation observers only see the type of its messages.
+    #[inline]
+    pub(super) fn new(owner: u64) -> Arc<Self> {
+        Self::with_fmt(owner, None)
+    }
+
+    /// Creates a new message bag wrapped in an [`Arc`] with an optional message formatter.
     #[inline]
-    pub(super) fn new() -> Arc<Self> {
-        Arc::new(Self(UnsafeCell::new(Vec::new())))
+    fn with_fmt(owner: u64, fmt: Option<MessageFmt<T>>) -> Arc<Self> {
+        Arc::new(Self {
+            values: UnsafeCell::new(Vec::new()),
+            owner,
+            fmt,
+        })
     }
 
     /// Returns a reference to the vector of messages in the bag.
@@ -73,10 +149,35 @@ impl<T> Bag<T> {
     }
 }
 
+impl<T: Debug> Bag<T> {
+    /// Creates a new message bag wrapped in an [`Arc`] for the component with the provided identifier.
+    /// Simulation observers see the [`Debug`] representation of its messages.
+    #[inline]
+    pub(super) fn new_traced(owner: u64) -> Arc<Self> {
+        Self::with_fmt(owner, Some(T::fmt))
+    }
+}
+
 impl<T> Deref for Bag<T> {
     type Target = UnsafeCell<Vec<T>>;
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.values
+    }
+}
+
+/// [`Debug`] representation of a message for simulation observers.
+/// Messages of ports without formatter are represented by the name of their type (e.g., `<u32>`).
+struct MessageRepr<'a, T> {
+    value: &'a T,
+    fmt: Option<MessageFmt<T>>,
+}
+
+impl<T> Debug for MessageRepr<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.fmt {
+            Some(fmt) => fmt(self.value, f),
+            None => write!(f, "<{}>", std::any::type_name::<T>()),
+        }
     }
 }
 
@@ -109,11 +210,184 @@ impl<T: DynRef + Clone> Port for Bag<T> {
         other.as_any().downcast_ref::<Bag<T>>().is_some()
     }
 
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    #[cfg(feature = "serde")]
+    #[inline]
+    fn new_empty(&self) -> Arc<dyn Port> {
+        Bag::<T>::with_fmt(0, self.fmt)
+    }
+
     #[inline]
     unsafe fn propagate(&self, port_to: &dyn Port) {
         let port_to = port_to.as_any().downcast_ref::<Bag<T>>().unwrap();
         port_to.borrow_mut().extend_from_slice(self.borrow());
     }
+
+    #[inline]
+    unsafe fn for_each_value(&self, f: &mut dyn FnMut(&dyn Debug)) {
+        let fmt = self.fmt;
+        for value in self.borrow() {
+            f(&MessageRepr { value, fmt });
+        }
+    }
+}
+
+/// Transformation of the messages propagated through a coupling.
+/// It allows coupling ports with different message types.
+pub(crate) trait Transform: DynRef {
+    /// Returns `true` if the transformation can receive messages from the port.
+    fn accepts(&self, port: &dyn Port) -> bool;
+
+    /// Returns `true` if the transformation can send messages to the port.
+    fn produces(&self, port: &dyn Port) -> bool;
+
+    /// Returns the name of the type of the messages received by the transformation.
+    fn input_type_name(&self) -> &'static str;
+
+    /// Returns the name of the type of the messages produced by the transformation.
+    fn output_type_name(&self) -> &'static str;
+
+    /// Creates a new empty port for the messages produced by the transformation.
+    fn new_output(&self) -> Arc<dyn Port>;
+
+    /// Transforms the messages of a port and adds the results to other receiving port.
+    /// It returns `true` if any message was added to the receiving port.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by a [`super::Coupled`] model when propagating
+    /// messages in its [`crate::simulation::Simulator`] trait implementation.
+    unsafe fn propagate(&self, port_from: &dyn Port, port_to: &dyn Port) -> bool;
+}
+
+/// Transformation that maps messages of type `A` to messages of type `B`.
+/// Messages mapped to [`None`] are filtered out.
+pub(crate) struct FilterMap<A, B, F> {
+    f: F,
+    _types: PhantomData<fn(&A) -> B>,
+}
+
+impl<A, B, F> FilterMap<A, B, F> {
+    /// Creates a new transformation wrapped in an [`Arc`].
+    #[inline]
+    pub(crate) fn new(f: F) -> Arc<Self> {
+        Arc::new(Self {
+            f,
+            _types: PhantomData,
+        })
+    }
+}
+
+impl<A, B, F> Transform for FilterMap<A, B, F>
+where
+    A: DynRef + Clone,
+    B: DynRef + Clone,
+    F: Fn(&A) -> Option<B> + DynRef,
+{
+    #[inline]
+    fn accepts(&self, port: &dyn Port) -> bool {
+        port.is_type::<A>()
+    }
+
+    #[inline]
+    fn produces(&self, port: &dyn Port) -> bool {
+        port.is_type::<B>()
+    }
+
+    #[inline]
+    fn input_type_name(&self) -> &'static str {
+        std::any::type_name::<A>()
+    }
+
+    #[inline]
+    fn output_type_name(&self) -> &'static str {
+        std::any::type_name::<B>()
+    }
+
+    #[inline]
+    fn new_output(&self) -> Arc<dyn Port> {
+        Bag::<B>::new(0)
+    }
+
+    #[inline]
+    unsafe fn propagate(&self, port_from: &dyn Port, port_to: &dyn Port) -> bool {
+        let port_from = port_from.as_any().downcast_ref::<Bag<A>>().unwrap();
+        let port_to = port_to.as_any().downcast_ref::<Bag<B>>().unwrap();
+        let values = port_to.borrow_mut();
+        let n = values.len();
+        values.extend(port_from.borrow().iter().filter_map(&self.f));
+        values.len() > n
+    }
+}
+
+/// Sequence of transformations. It results from flattening chains of couplings with transformations.
+/// Intermediate messages are stored in auxiliary ports, which are cleared after every propagation.
+pub(crate) struct Chain {
+    transforms: Vec<Arc<dyn Transform>>,
+    buffers: Vec<Arc<dyn Port>>,
+}
+
+impl Chain {
+    /// Combines a sequence of transformations into a single transformation wrapped in an [`Arc`].
+    /// If there is only one transformation, it is returned as is. If there are none, it returns [`None`].
+    /// The output of each transformation must be compatible with the input of the next one.
+    pub(crate) fn combine(transforms: Vec<Arc<dyn Transform>>) -> Option<Arc<dyn Transform>> {
+        match transforms.len() {
+            0 => None,
+            1 => transforms.into_iter().next(),
+            n => {
+                let buffers = transforms[..n - 1].iter().map(|t| t.new_output()).collect();
+                // Transformations and ports are only required to be Send and Sync with par_any
+                #[allow(clippy::arc_with_non_send_sync)]
+                Some(Arc::new(Self {
+                    transforms,
+                    buffers,
+                }))
+            }
+        }
+    }
+}
+
+impl Transform for Chain {
+    #[inline]
+    fn accepts(&self, port: &dyn Port) -> bool {
+        self.transforms[0].accepts(port)
+    }
+
+    #[inline]
+    fn produces(&self, port: &dyn Port) -> bool {
+        self.transforms.last().unwrap().produces(port)
+    }
+
+    #[inline]
+    fn input_type_name(&self) -> &'static str {
+        self.transforms[0].input_type_name()
+    }
+
+    #[inline]
+    fn output_type_name(&self) -> &'static str {
+        self.transforms.last().unwrap().output_type_name()
+    }
+
+    #[inline]
+    fn new_output(&self) -> Arc<dyn Port> {
+        self.transforms.last().unwrap().new_output()
+    }
+
+    unsafe fn propagate(&self, port_from: &dyn Port, port_to: &dyn Port) -> bool {
+        let mut from = port_from;
+        for (transform, buffer) in self.transforms.iter().zip(&self.buffers) {
+            transform.propagate(from, &**buffer);
+            from = &**buffer;
+        }
+        let received = self.transforms.last().unwrap().propagate(from, port_to);
+        self.buffers.iter().for_each(|buffer| buffer.clear());
+        received
+    }
 }
 
 /// Input port. This structure only allows reading messages. Thus, it cannot inject messages.
@@ -128,6 +402,7 @@ impl<T: Clone> InPort<T> {
     ///
     /// This method can only be called when implementing the [`super::Atomic::delta_ext`] method.
     /// Furthermore, this port must be one of the input ports of the implementer.
+    /// Prefer the safe [`InputCtx::is_empty`] method instead.
     #[inline]
     pub unsafe fn is_empty(&self) -> bool {
         self.0.borrow().is_empty()
@@ -139,10 +414,18 @@ impl<T: Clone> InPort<T> {
     ///
     /// This method can only be called when implementing the [`super::Atomic::delta_ext`] method.
     /// Furthermore, this port must be one of the input ports of the implementer.
+    /// Prefer the safe [`InputCtx::get_values`] method instead.
     #[inline]
     pub unsafe fn get_values(&self) -> &[T] {
         self.0.borrow()
     }
+
+    /// Returns the typed identifier of the port. It is used for adding couplings
+    /// whose message types are checked at compile time (e.g., [`super::Coupled::add_typed_ic`]).
+    #[inline]
+    pub fn id(&self) -> InPortId<T> {
+        InPortId(self.0.clone())
+    }
 }
 
 /// Output port. This structure only injecting messages. Thus, it cannot read messages.
@@ -157,6 +440,7 @@ impl<T: Clone> OutPort<T> {
     ///
     /// This method can only be called when implementing the [`super::Atomic::lambda`] method.
     /// Furthermore, this port must be one of the output ports of the implementer.
+    /// Prefer the safe [`OutputCtx::add_value`] method instead.
     #[inline]
     pub unsafe fn add_value(&self, value: T) {
         self.0.borrow_mut().push(value);
@@ -168,8 +452,176 @@ impl<T: Clone> OutPort<T> {
     ///
     /// This method can only be called when implementing the [`super::Atomic::lambda`] method.
     /// Furthermore, this port must be one of the output ports of the implementer.
+    /// Prefer the safe [`OutputCtx::add_values`] method instead.
     #[inline]
     pub unsafe fn add_values(&self, values: &[T]) {
         self.0.borrow_mut().extend_from_slice(values);
     }
+
+    /// Returns the typed identifier of the port. It is used for adding couplings
+    /// whose message types are checked at compile time (e.g., [`super::Coupled::add_typed_ic`]).
+    #[inline]
+    pub fn id(&self) -> OutPortId<T> {
+        OutPortId(self.0.clone())
+    }
+}
+
+/// Typed identifier of an [`InPort`]. It does not allow reading nor sending messages,
+/// so it can be freely cloned and used after the owner of the port is added to a coupled model.
+#[derive(Debug)]
+pub struct InPortId<T>(Arc<Bag<T>>);
+
+impl<T> InPortId<T> {
+    /// Returns the address of the port. It is used for identifying ports.
+    #[inline]
+    pub(crate) fn address(&self) -> *const () {
+        Arc::as_ptr(&self.0).cast()
+    }
+}
+
+impl<T> Clone for InPortId<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Typed identifier of an [`OutPort`]. It does not allow reading nor sending messages,
+/// so it can be freely cloned and used after the owner of the port is added to a coupled model.
+#[derive(Debug)]
+pub struct OutPortId<T>(Arc<Bag<T>>);
+
+impl<T> OutPortId<T> {
+    /// Returns the address of the port. It is used for identifying ports.
+    #[inline]
+    pub(crate) fn address(&self) -> *const () {
+        Arc::as_ptr(&self.0).cast()
+    }
+}
+
+impl<T> Clone for OutPortId<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Context of the output function of an atomic model (see [`super::Atomic::lambda`]).
+/// It is the only way to safely add messages to [`OutPort`]s.
+/// Contexts can only be created by the simulator, so models can only send messages
+/// when the DEVS simulation algorithm allows it. Furthermore, contexts only accept
+/// the ports of the model that received them.
+#[derive(Debug)]
+pub struct OutputCtx {
+    /// Identifier of the component of the model that received this context.
+    owner: u64,
+}
+
+impl OutputCtx {
+    /// Creates a new output context for a model.
+    /// It must only be created by a [`crate::simulation::Simulator`] when calling [`super::Atomic::lambda`].
+    #[inline]
+    pub(crate) fn new<T: Time>(component: &Component<T>) -> Self {
+        Self {
+            owner: component.id(),
+        }
+    }
+
+    /// Adds a new value to an output port.
+    /// It panics if the port is not one of the output ports of the model that received this context.
+    #[inline]
+    pub fn add_value<T: Clone>(&self, port: &OutPort<T>, value: T) {
+        check_owner(self.owner, &port.0);
+        // Safety: adding messages to an output port of the model in the output function
+        unsafe { port.add_value(value) };
+    }
+
+    /// Adds new values from a slice to an output port.
+    /// It panics if the port is not one of the output ports of the model that received this context.
+    #[inline]
+    pub fn add_values<T: Clone>(&self, port: &OutPort<T>, values: &[T]) {
+        check_owner(self.owner, &port.0);
+        // Safety: adding messages to an output port of the model in the output function
+        unsafe { port.add_values(values) };
+    }
+}
+
+/// Context of the external and confluent transition functions of an atomic model
+/// (see [`super::Atomic::delta_ext`] and [`super::Atomic::delta_conf`]).
+/// It is the only way to safely read messages from [`InPort`]s.
+/// Contexts can only be created by the simulator, so models can only read messages
+/// when the DEVS simulation algorithm allows it. Furthermore, contexts only accept
+/// the ports of the model that received them.
+#[derive(Debug)]
+pub struct InputCtx {
+    /// Identifier of the component of the model that received this context.
+    owner: u64,
+}
+
+impl InputCtx {
+    /// Creates a new input context for a model. It must only be created by a [`crate::simulation::Simulator`]
+    /// when calling [`super::Atomic::delta_ext`] or [`super::Atomic::delta_conf`].
+    #[inline]
+    pub(crate) fn new<T: Time>(component: &Component<T>) -> Self {
+        Self {
+            owner: component.id(),
+        }
+    }
+
+    /// Returns `true` if an input port is empty. Otherwise, it returns `false`.
+    /// It panics if the port is not one of the input ports of the model that received this context.
+    #[inline]
+    pub fn is_empty<T: Clone>(&self, port: &InPort<T>) -> bool {
+        check_owner(self.owner, &port.0);
+        // Safety: reading messages from an input port of the model in the transition function
+        unsafe { port.is_empty() }
+    }
+
+    /// Returns a reference to the slice of messages of an input port.
+    /// It panics if the port is not one of the input ports of the model that received this context.
+    #[inline]
+    pub fn get_values<'a, T: Clone>(&'a self, port: &'a InPort<T>) -> &'a [T] {
+        check_owner(self.owner, &port.0);
+        // Safety: reading messages from an input port of the model in the transition function
+        unsafe { port.get_values() }
+    }
+}
+
+/// Checks that a port belongs to the component with the provided identifier.
+/// Otherwise, other models could access the port while they are executed in parallel.
+#[inline]
+fn check_owner<T>(owner: u64, bag: &Bag<T>) {
+    if bag.owner != owner {
+        panic!("port does not belong to the model that received the context");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_repr() {
+        let repr = |port: Arc<dyn Port>| {
+            let mut values = Vec::new();
+            // Safety: no simulation is running
+            unsafe {
+                port.inject(&[1u32, 2]);
+                port.for_each_value(&mut |value| values.push(format!("{value:?}")));
+            }
+            values
+        };
+        assert_eq!(vec!["1", "2"], repr(Bag::<u32>::new_traced(0)));
+        assert_eq!(vec!["<u32>", "<u32>"], repr(Bag::<u32>::new(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "port does not belong to the model that received the context")]
+    fn test_port_owner() {
+        let (mut a, mut b) = (Component::<f64>::new("a"), Component::<f64>::new("b"));
+        let input = a.add_in_port::<u32>("input");
+        let output = b.add_out_port::<u32>("output");
+        assert!(InputCtx::new(&a).is_empty(&input));
+        OutputCtx::new(&b).add_value(&output, 1);
+        // models cannot send messages through the ports of other models
+        OutputCtx::new(&a).add_value(&output, 2);
+    }
 }
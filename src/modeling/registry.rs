@@ -0,0 +1,672 @@
+use super::port::{Bag, Port};
+use super::{Coupled, Time};
+use crate::simulation::Simulator;
+use crate::{DynRef, Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Constructor of a registered model type. It receives the name and the parameters of the new model.
+type Constructor<T> = Box<dyn Fn(&str, Value) -> Result<Box<dyn Simulator<T>>> + Send + Sync>;
+
+/// Description of a port in the JSON structure of a model.
+#[derive(Debug, Serialize, Deserialize)]
+struct PortDesc {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+}
+
+/// Description of the ports of a model in the JSON structure of a model.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PortsDesc {
+    #[serde(rename = "in", default, skip_serializing_if = "Vec::is_empty")]
+    in_ports: Vec<PortDesc>,
+    #[serde(rename = "out", default, skip_serializing_if = "Vec::is_empty")]
+    out_ports: Vec<PortDesc>,
+}
+
+/// Description of a coupling in the JSON structure of a model.
+/// Missing components refer to the coupled model itself.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CouplingDesc {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    component_from: Option<String>,
+    port_from: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    component_to: Option<String>,
+    port_to: String,
+}
+
+/// Description of a component in the JSON structure of a model.
+/// Components with a `component_id` are built with the registry.
+/// Otherwise, they are coupled models described by their `components` and `couplings`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ComponentDesc {
+    #[serde(alias = "type", default, skip_serializing_if = "Option::is_none")]
+    component_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<Value>,
+    #[serde(alias = "params", default, skip_serializing_if = "Map::is_empty")]
+    kwargs: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ports: Option<PortsDesc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    components: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    couplings: Vec<CouplingDesc>,
+}
+
+/// Creates a new empty port of type `V`.
+fn new_port<V: DynRef + Clone>() -> Arc<dyn Port> {
+    Bag::<V>::new(0)
+}
+
+/// Returns an error for an invalid description of a model.
+fn invalid(message: impl ToString) -> Error {
+    Error::InvalidDescription(message.to_string())
+}
+
+/// Registry of DEVS model types. It is used for building DEVS models from descriptions
+/// of their components, parameters, and couplings, and for exporting the structure of DEVS models.
+/// Descriptions follow the JSON format of the xDEVS Python and Java implementations:
+///
+/// ```json
+/// {
+///   "root": {
+///     "components": {
+///       "generator": {"component_id": "generator", "kwargs": {"period": 1.0}},
+///       "sub": {"components": {...}, "couplings": [...]}
+///     },
+///     "couplings": [
+///       {"componentFrom": "generator", "portFrom": "output", "componentTo": "sub", "portTo": "input"},
+///       {"componentFrom": "sub", "portFrom": "output", "portTo": "output"}
+///     ]
+///   }
+/// }
+/// ```
+///
+/// Components with a `component_id` (or `type`) are created with the constructor registered for that ID,
+/// which receives the name of the component and its `kwargs` (or `params`).
+/// If there are no `kwargs`, it receives its `args` instead.
+/// Components without a `component_id` are coupled models. Couplings without `componentFrom`
+/// (or `componentTo`) refer to the ports of the coupled model itself.
+/// Exported models also describe the `ports` of every component, with their name and message type.
+///
+/// Descriptions can also be written in TOML (with the `toml` feature) or YAML (with the `yaml` feature).
+pub struct ModelRegistry<T: Time = f64> {
+    /// Constructors of the registered model types, indexed by their ID.
+    constructors: HashMap<String, Constructor<T>>,
+    /// IDs of the registered model types, indexed by the name of their Rust type.
+    ids: HashMap<&'static str, String>,
+    /// Constructors of ports of the registered message types, indexed by the name of their Rust type.
+    ports: HashMap<&'static str, fn() -> Arc<dyn Port>>,
+}
+
+impl<T: Time> Default for ModelRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Time> ModelRegistry<T> {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        Self {
+            constructors: HashMap::new(),
+            ids: HashMap::new(),
+            ports: HashMap::new(),
+        }
+    }
+
+    /// Registers a model type with the given ID. Both atomic and coupled model types can be registered.
+    /// The constructor receives the name of the new model and its parameters, which are deserialized
+    /// from the description of the model. Parameters can be any deserializable type, such as
+    /// a custom structure or a parameter map (e.g., `HashMap<String, serde_json::Value>`).
+    /// The new model is renamed after the name of the component in the description.
+    /// If there is already a model type with the same ID, it is replaced.
+    pub fn register<M, P, F>(&mut self, component_id: &str, constructor: F)
+    where
+        M: Simulator<T>,
+        P: DeserializeOwned,
+        F: Fn(&str, P) -> M + Send + Sync + 'static,
+    {
+        self.ids
+            .insert(std::any::type_name::<M>(), component_id.to_string());
+        let constructor = move |name: &str, params: Value| -> Result<Box<dyn Simulator<T>>> {
+            let params = serde_json::from_value(params).map_err(|e| Error::InvalidParameters {
+                component: name.to_string(),
+                message: e.to_string(),
+            })?;
+            let mut model = Box::new(constructor(name, params));
+            model.get_component_mut().set_name(name);
+            Ok(model)
+        };
+        self.constructors
+            .insert(component_id.to_string(), Box::new(constructor));
+    }
+
+    /// Registers a message type for the ports of coupled models.
+    /// When importing a model, the message type of the ports of coupled models is inferred from their couplings.
+    /// Registered message types are only required for ports without couplings.
+    pub fn register_port_type<V: DynRef + Clone>(&mut self) {
+        self.ports.insert(std::any::type_name::<V>(), new_port::<V>);
+    }
+
+    /// Returns the JSON description of the structure of a coupled model and all its subcomponents.
+    /// Atomic models are identified by the ID of their registered type.
+    /// If their type is not registered, they are identified by the name of their Rust type.
+    /// Note that the parameters of the models and the transformations of couplings are not exported.
+    pub fn to_json(&self, coupled: &Coupled<T>) -> Value {
+        let desc = self.describe(coupled);
+        let mut json = Map::new();
+        let name = coupled.get_component().get_name().to_string();
+        json.insert(name, serde_json::to_value(desc).unwrap());
+        Value::Object(json)
+    }
+
+    /// Returns the description of a model and all its subcomponents.
+    fn describe(&self, model: &dyn Simulator<T>) -> ComponentDesc {
+        let port_descs = |ports: Vec<(String, Arc<dyn Port>)>| {
+            ports
+                .into_iter()
+                .map(|(name, port)| PortDesc {
+                    name,
+                    type_name: port.type_name().to_string(),
+                })
+                .collect()
+        };
+        let ports = PortsDesc {
+            in_ports: port_descs(model.get_component().get_in_ports()),
+            out_ports: port_descs(model.get_component().get_out_ports()),
+        };
+        let mut desc = ComponentDesc {
+            ports: Some(ports),
+            ..Default::default()
+        };
+        match model.as_coupled() {
+            Some(coupled) => {
+                let components = coupled.components.iter().map(|c| {
+                    let component = serde_json::to_value(self.describe(&**c)).unwrap();
+                    (c.get_name().to_string(), component)
+                });
+                desc.components = Some(components.collect());
+                let couplings = coupled.eics.iter().chain(&coupled.ics).chain(&coupled.eocs);
+                desc.couplings = couplings
+                    .map(|c| {
+                        let info = c.info();
+                        CouplingDesc {
+                            component_from: info.component_from.map(str::to_string),
+                            port_from: info.port_from.to_string(),
+                            component_to: info.component_to.map(str::to_string),
+                            port_to: info.port_to.to_string(),
+                        }
+                    })
+                    .collect();
+            }
+            None => {
+                let type_name = model.type_name();
+                let id = self.ids.get(type_name).map_or(type_name, String::as_str);
+                desc.component_id = Some(id.to_string());
+            }
+        }
+        desc
+    }
+
+    /// Creates a coupled model from its JSON description.
+    /// The description must be an object with a single entry, whose key is the name of the coupled model.
+    /// It returns an error if the description is not valid, a component type is not registered,
+    /// the parameters of a component are not valid, or the resulting model is not valid.
+    pub fn from_json(&self, json: &Value) -> Result<Coupled<T>> {
+        let (name, desc) = match json.as_object() {
+            Some(json) if json.len() == 1 => json.iter().next().unwrap(),
+            _ => return Err(invalid("expected an object with a single coupled model")),
+        };
+        let desc = serde_json::from_value(desc.clone()).map_err(invalid)?;
+        self.build_coupled(name, desc)
+    }
+
+    /// Creates a coupled model from its TOML description.
+    /// The description is the TOML equivalent of the JSON description (see [`ModelRegistry::from_json`]).
+    #[cfg(feature = "toml")]
+    pub fn from_toml(&self, toml: &str) -> Result<Coupled<T>> {
+        self.from_json(&toml::from_str(toml).map_err(invalid)?)
+    }
+
+    /// Creates a coupled model from its YAML description.
+    /// The description is the YAML equivalent of the JSON description (see [`ModelRegistry::from_json`]).
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(&self, yaml: &str) -> Result<Coupled<T>> {
+        self.from_json(&serde_yaml::from_str(yaml).map_err(invalid)?)
+    }
+
+    /// Creates a model from its description.
+    fn build(&self, name: &str, desc: ComponentDesc) -> Result<Box<dyn Simulator<T>>> {
+        let Some(component_id) = desc.component_id else {
+            return Ok(Box::new(self.build_coupled(name, desc)?));
+        };
+        let constructor =
+            self.constructors
+                .get(&component_id)
+                .ok_or_else(|| Error::UnknownComponentType {
+                    component: name.to_string(),
+                    component_id: component_id.clone(),
+                })?;
+        let params = match (desc.kwargs.is_empty(), desc.args.is_empty()) {
+            (true, false) => Value::Array(desc.args),
+            _ => Value::Object(desc.kwargs),
+        };
+        constructor(name, params)
+    }
+
+    /// Creates a coupled model from its description.
+    fn build_coupled(&self, name: &str, desc: ComponentDesc) -> Result<Coupled<T>> {
+        let components = desc
+            .components
+            .ok_or_else(|| invalid(format!("component {name} has no type nor components")))?;
+        let mut coupled = Coupled::new(name);
+        for (component_name, component) in components {
+            let component = serde_json::from_value(component).map_err(invalid)?;
+            let component = self.build(&component_name, component)?;
+            coupled.try_add_dyn_component(component)?;
+        }
+        // Ports of the coupled model are declared first, and then inferred from the couplings
+        let ports = desc.ports.unwrap_or_default();
+        let declared = ports.in_ports.iter().map(|p| (&p.name, Some(&p.type_name)));
+        let eics = desc.couplings.iter().filter(|c| c.component_from.is_none());
+        for (port, type_name) in declared.chain(eics.map(|c| (&c.port_from, None))) {
+            if coupled.component.get_in_port(port).is_none() {
+                let new_port = self.new_port(&coupled, &desc.couplings, port, type_name, true)?;
+                coupled.component.add_dyn_in_port(port, new_port)?;
+            }
+        }
+        let declared = ports
+            .out_ports
+            .iter()
+            .map(|p| (&p.name, Some(&p.type_name)));
+        let eocs = desc.couplings.iter().filter(|c| c.component_to.is_none());
+        for (port, type_name) in declared.chain(eocs.map(|c| (&c.port_to, None))) {
+            if coupled.component.get_out_port(port).is_none() {
+                let new_port = self.new_port(&coupled, &desc.couplings, port, type_name, false)?;
+                coupled.component.add_dyn_out_port(port, new_port)?;
+            }
+        }
+        for c in desc.couplings.iter() {
+            let (port_from, port_to) = (c.port_from.as_str(), c.port_to.as_str());
+            match (c.component_from.as_deref(), c.component_to.as_deref()) {
+                (None, Some(component_to)) => {
+                    coupled.try_add_eic(port_from, component_to, port_to)?
+                }
+                (Some(component_from), Some(component_to)) => {
+                    coupled.try_add_ic(component_from, port_from, component_to, port_to)?
+                }
+                (Some(component_from), None) => {
+                    coupled.try_add_eoc(component_from, port_from, port_to)?
+                }
+                (None, None) => {
+                    return Err(invalid(format!(
+                        "coupling from {name}.{port_from} to {name}.{port_to} is not supported"
+                    )))
+                }
+            }
+        }
+        Ok(coupled)
+    }
+
+    /// Creates a new port for a coupled model. If the message type of the port is registered,
+    /// it creates a port of that type. Otherwise, it infers the message type from the couplings
+    /// between the port and the subcomponents of the coupled model.
+    fn new_port(
+        &self,
+        coupled: &Coupled<T>,
+        couplings: &[CouplingDesc],
+        port: &str,
+        type_name: Option<&String>,
+        is_input: bool,
+    ) -> Result<Arc<dyn Port>> {
+        if let Some(new_port) = type_name.and_then(|t| self.ports.get(t.as_str())) {
+            return Ok(new_port());
+        }
+        let component = |name: &str| coupled.components.iter().find(|c| c.get_name() == name);
+        let inferred = couplings.iter().find_map(|c| match is_input {
+            true if c.component_from.is_none() && c.port_from == port => {
+                let component = component(c.component_to.as_deref()?)?;
+                component.get_component().get_in_port(&c.port_to)
+            }
+            false if c.component_to.is_none() && c.port_to == port => {
+                let component = component(c.component_from.as_deref()?)?;
+                component.get_component().get_out_port(&c.port_from)
+            }
+            _ => None,
+        });
+        match inferred {
+            Some(p) => Ok(p.new_empty()),
+            None => Err(Error::UnknownPortType {
+                component: coupled.component.get_name().to_string(),
+                port: port.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, HasComponent, InPort, InputCtx, OutPort, OutputCtx};
+    use crate::simulation::RootCoordinator;
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    #[test]
+    fn test_registry_json() {
+        use crate::modeling::ModelRegistry;
+        use crate::Error;
+
+        #[derive(serde::Deserialize)]
+        struct GeneratorParams {
+            period: f64,
+        }
+
+        let mut registry = ModelRegistry::new();
+        registry.register("generator", |name, params: GeneratorParams| {
+            Generator::new(name, params.period)
+        });
+        let json = serde_json::json!({
+            "root": {
+                "components": {
+                    "sub": {
+                        "components": {
+                            "generator": {"component_id": "generator", "kwargs": {"period": 2.0}}
+                        },
+                        "couplings": [
+                            {"portFrom": "input", "componentTo": "generator", "portTo": "input"},
+                            {"componentFrom": "generator", "portFrom": "output", "portTo": "output"}
+                        ]
+                    }
+                },
+                "couplings": [
+                    {"portFrom": "input", "componentTo": "sub", "portTo": "input"},
+                    {"componentFrom": "sub", "portFrom": "output", "portTo": "output"}
+                ]
+            }
+        });
+        let coupled = registry.from_json(&json).unwrap();
+        assert!(coupled.validate().is_empty());
+
+        let exported = registry.to_json(&coupled);
+        let ports = |input: &str, output: &str| {
+            serde_json::json!({
+                "in": [{"name": "input", "type": input}],
+                "out": [{"name": "output", "type": output}]
+            })
+        };
+        assert_eq!(
+            "generator",
+            exported["root"]["components"]["sub"]["components"]["generator"]["component_id"]
+        );
+        assert_eq!(ports("bool", "usize"), exported["root"]["ports"]);
+        assert_eq!(json["root"]["couplings"], exported["root"]["couplings"]);
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<usize>("output");
+        simulator.simulate(5.);
+        assert_eq!(
+            &[(2., 0), (4., 1)],
+            simulator.get_output::<usize>("output").unwrap()
+        );
+
+        let json = serde_json::json!({"root": {"components": {"a": {"component_id": "other"}}}});
+        let err = registry.from_json(&json).err().unwrap();
+        let expected = Error::UnknownComponentType {
+            component: "a".to_string(),
+            component_id: "other".to_string(),
+        };
+        assert_eq!(expected, err);
+        let json =
+            serde_json::json!({"root": {"components": {"a": {"component_id": "generator"}}}});
+        let err = registry.from_json(&json).err().unwrap();
+        assert!(matches!(err, Error::InvalidParameters { .. }));
+    }
+
+    #[test]
+    fn test_registry_config() {
+        use crate::modeling::ModelRegistry;
+        use std::collections::HashMap;
+
+        let mut registry = ModelRegistry::new();
+        registry.register("generator", |name, params: HashMap<String, f64>| {
+            Generator::new(name, params["period"])
+        });
+        // Coupled model types are renamed after their component in the description
+        registry.register("root", |_, _: HashMap<String, f64>| create_model());
+
+        let check = |coupled: Coupled| {
+            assert_eq!("top", coupled.get_component().get_name());
+            assert!(coupled.validate().is_empty());
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.collect_output::<usize>("output");
+            simulator.simulate(5.);
+            assert_eq!(
+                &[(2., 0), (3., 0), (4., 1)],
+                simulator.get_output::<usize>("output").unwrap()
+            );
+        };
+
+        let json = serde_json::json!({
+            "top": {
+                "components": {
+                    "gen": {"type": "generator", "params": {"period": 3.0}},
+                    "model": {"type": "root"}
+                },
+                "couplings": [
+                    {"componentFrom": "gen", "portFrom": "output", "portTo": "output"},
+                    {"componentFrom": "model", "portFrom": "output", "portTo": "output"},
+                    {"portFrom": "input", "componentTo": "model", "portTo": "input"},
+                    {"portFrom": "input", "componentTo": "gen", "portTo": "input"}
+                ]
+            }
+        });
+        check(registry.from_json(&json).unwrap());
+
+        #[cfg(feature = "toml")]
+        {
+            let toml = r#"
+                [top.components.gen]
+                type = "generator"
+                params = { period = 3.0 }
+
+                [top.components.model]
+                type = "root"
+
+                [[top.couplings]]
+                componentFrom = "gen"
+                portFrom = "output"
+                portTo = "output"
+
+                [[top.couplings]]
+                componentFrom = "model"
+                portFrom = "output"
+                portTo = "output"
+
+                [[top.couplings]]
+                portFrom = "input"
+                componentTo = "model"
+                portTo = "input"
+
+                [[top.couplings]]
+                portFrom = "input"
+                componentTo = "gen"
+                portTo = "input"
+            "#;
+            check(registry.from_toml(toml).unwrap());
+            assert!(matches!(
+                registry.from_toml("top = ["),
+                Err(crate::Error::InvalidDescription(_))
+            ));
+        }
+
+        #[cfg(feature = "yaml")]
+        {
+            let yaml = r#"
+                top:
+                  components:
+                    gen: {type: generator, params: {period: 3.0}}
+                    model: {type: root}
+                  couplings:
+                    - {componentFrom: gen, portFrom: output, portTo: output}
+                    - {componentFrom: model, portFrom: output, portTo: output}
+                    - {portFrom: input, componentTo: model, portTo: input}
+                    - {portFrom: input, componentTo: gen, portTo: input}
+            "#;
+            check(registry.from_yaml(yaml).unwrap());
+            assert!(matches!(
+                registry.from_yaml("top: ["),
+                Err(crate::Error::InvalidDescription(_))
+            ));
+        }
+
+        let mut registry = ModelRegistry::new();
+        crate::devstone::register(&mut registry);
+        let json = serde_json::json!({
+            "bench": {"components": {"li": {"type": "LI", "params": {"width": 2, "depth": 3}}}}
+        });
+        let coupled = registry.from_json(&json).unwrap();
+        assert_eq!("li", coupled.components[0].get_name());
+        // DEVStone models leave some ports unconnected on purpose
+        RootCoordinator::unvalidated(coupled).simulate(f64::INFINITY);
+    }
+
+    #[test]
+    fn test_registry_errors() {
+        use crate::modeling::ModelRegistry;
+        use crate::Error;
+
+        let mut registry = ModelRegistry::new();
+        registry.register("generator", |name, (period,): (f64,)| {
+            Generator::new(name, period)
+        });
+
+        // Positional parameters are passed as args
+        let json = serde_json::json!({
+            "root": {"components": {"gen": {"component_id": "generator", "args": [1.0]}}}
+        });
+        assert_eq!(1, registry.from_json(&json).unwrap().n_components());
+
+        let json = serde_json::json!({"a": {"components": {}}, "b": {"components": {}}});
+        let err = registry.from_json(&json).err().unwrap();
+        assert!(matches!(err, Error::InvalidDescription(_)));
+        let json = serde_json::json!({"root": {"couplings": []}});
+        let err = registry.from_json(&json).err().unwrap();
+        let expected =
+            Error::InvalidDescription("component root has no type nor components".into());
+        assert_eq!(expected, err);
+        // Couplings are checked as with the try_add_* methods
+        let json = serde_json::json!({
+            "root": {
+                "components": {"gen": {"component_id": "generator", "args": [1.0]}},
+                "couplings": [{"componentFrom": "gen", "portFrom": "output", "componentTo": "other", "portTo": "input"}]
+            }
+        });
+        let err = registry.from_json(&json).err().unwrap();
+        let expected = Error::ComponentNotFound {
+            coupled: "root".to_string(),
+            component: "other".to_string(),
+        };
+        assert_eq!(expected, err);
+
+        // The type of ports without couplings must be registered
+        let json = serde_json::json!({
+            "root": {"components": {}, "ports": {"in": [{"name": "input", "type": "u8"}]}}
+        });
+        let err = registry.from_json(&json).err().unwrap();
+        let expected = Error::UnknownPortType {
+            component: "root".to_string(),
+            port: "input".to_string(),
+        };
+        assert_eq!(expected, err);
+        registry.register_port_type::<u8>();
+        let coupled = registry.from_json(&json).unwrap();
+        assert_eq!(
+            "u8",
+            coupled
+                .get_component()
+                .get_in_port("input")
+                .unwrap()
+                .type_name()
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Interface for saving and restoring the state of atomic DEVS models.
+/// It is used by [`crate::simulation::RootCoordinator::save`] and
+/// [`crate::simulation::RootCoordinator::restore`] to checkpoint running simulations.
+///
+/// Implementers must also override [`super::Atomic::as_snapshot`] and
+/// [`super::Atomic::as_snapshot_mut`] so the simulator can find this implementation.
+/// Note that the simulation times of the model are saved by the simulator,
+/// so they do not need to be part of [`Snapshot::State`].
+pub trait Snapshot {
+    /// Serializable representation of the state of the model.
+    type State: Serialize + DeserializeOwned;
+
+    /// Returns the current state of the model.
+    fn save_state(&self) -> Self::State;
+
+    /// Sets the state of the model to a previously saved state.
+    fn restore_state(&mut self, state: Self::State);
+}
+
+/// Object-safe version of [`Snapshot`]. It is automatically implemented for all the [`Snapshot`] types.
+pub trait DynSnapshot {
+    /// Returns the current state of the model as a JSON value.
+    fn save_value(&self) -> serde_json::Result<Value>;
+
+    /// Sets the state of the model from a JSON value.
+    fn restore_value(&mut self, state: Value) -> serde_json::Result<()>;
+
+    /// Checks that a JSON value is a valid state of the model without modifying the model.
+    fn check_value(&self, state: &Value) -> serde_json::Result<()>;
+}
+
+impl<T: Snapshot> DynSnapshot for T {
+    fn save_value(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self.save_state())
+    }
+
+    fn restore_value(&mut self, state: Value) -> serde_json::Result<()> {
+        self.restore_state(serde_json::from_value(state)?);
+        Ok(())
+    }
+
+    fn check_value(&self, state: &Value) -> serde_json::Result<()> {
+        T::State::deserialize(state).map(|_| ())
+    }
+}
@@ -0,0 +1,334 @@
+use super::Time;
+use crate::simulation::Simulator;
+use std::sync::{Arc, Mutex};
+
+/// Structural change requested through a [`StructureHandle`].
+pub(crate) enum StructuralChange<T: Time> {
+    AddComponent(Box<dyn Simulator<T>>),
+    RemoveComponent(String),
+    AddEic(String, String, String),
+    RemoveEic(String, String, String),
+    AddIc(String, String, String, String),
+    RemoveIc(String, String, String, String),
+    AddEoc(String, String, String),
+    RemoveEoc(String, String, String),
+}
+
+/// Queue of structural changes shared by a coupled model and its handles.
+pub(crate) type ChangeQueue<T> = Arc<Mutex<Vec<StructuralChange<T>>>>;
+
+/// Handle for requesting structural changes to a [`super::Coupled`] model during the simulation.
+/// Handles are created with [`super::Coupled::structure_handle`] and can be cloned and
+/// stored in any model (e.g., a network executive that creates and destroys entities).
+///
+/// Changes are not applied immediately. Instead, they are queued and applied in order by the
+/// coupled model at the end of its next state transition, when all the ports are empty.
+/// Thus, changes requested in the output or transition functions of a subcomponent are applied
+/// in the same simulation step. New components are started at the current simulation time,
+/// and removed components are stopped. Invalid changes (e.g., adding a coupling between
+/// ports that do not exist) are discarded and reported by
+/// [`RootCoordinator::try_step`](crate::simulation::RootCoordinator::try_step).
+pub struct StructureHandle<T: Time = f64>(pub(crate) ChangeQueue<T>);
+
+impl<T: Time> Clone for StructureHandle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Time> StructureHandle<T> {
+    /// Queues a structural change.
+    #[inline]
+    fn push(&self, change: StructuralChange<T>) {
+        self.0.lock().unwrap().push(change);
+    }
+
+    /// Requests adding a new component to the coupled model.
+    pub fn add_component<M: Simulator<T>>(&self, component: Box<M>) {
+        self.push(StructuralChange::AddComponent(component));
+    }
+
+    /// Requests removing a component from the coupled model, together with all its couplings.
+    pub fn remove_component(&self, name: &str) {
+        self.push(StructuralChange::RemoveComponent(name.to_string()));
+    }
+
+    /// Requests adding a new EIC to the coupled model.
+    pub fn add_eic(&self, port_from: &str, component_to: &str, port_to: &str) {
+        self.push(StructuralChange::AddEic(
+            port_from.to_string(),
+            component_to.to_string(),
+            port_to.to_string(),
+        ));
+    }
+
+    /// Requests removing an EIC from the coupled model.
+    pub fn remove_eic(&self, port_from: &str, component_to: &str, port_to: &str) {
+        self.push(StructuralChange::RemoveEic(
+            port_from.to_string(),
+            component_to.to_string(),
+            port_to.to_string(),
+        ));
+    }
+
+    /// Requests adding a new IC to the coupled model.
+    pub fn add_ic(&self, component_from: &str, port_from: &str, component_to: &str, port_to: &str) {
+        self.push(StructuralChange::AddIc(
+            component_from.to_string(),
+            port_from.to_string(),
+            component_to.to_string(),
+            port_to.to_string(),
+        ));
+    }
+
+    /// Requests removing an IC from the coupled model.
+    pub fn remove_ic(
+        &self,
+        component_from: &str,
+        port_from: &str,
+        component_to: &str,
+        port_to: &str,
+    ) {
+        self.push(StructuralChange::RemoveIc(
+            component_from.to_string(),
+            port_from.to_string(),
+            component_to.to_string(),
+            port_to.to_string(),
+        ));
+    }
+
+    /// Requests adding a new EOC to the coupled model.
+    pub fn add_eoc(&self, component_from: &str, port_from: &str, port_to: &str) {
+        self.push(StructuralChange::AddEoc(
+            component_from.to_string(),
+            port_from.to_string(),
+            port_to.to_string(),
+        ));
+    }
+
+    /// Requests removing an EOC from the coupled model.
+    pub fn remove_eoc(&self, component_from: &str, port_from: &str, port_to: &str) {
+        self.push(StructuralChange::RemoveEoc(
+            component_from.to_string(),
+            port_from.to_string(),
+            port_to.to_string(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modeling::{
+        Atomic, Component, Coupled, HasComponent, InPort, InputCtx, OutPort, OutputCtx,
+        StructureHandle,
+    };
+    use crate::simulation::{RootCoordinator, SimObserver};
+    use crate::Error;
+    use std::sync::{Arc, Mutex};
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    /// Simulation observer that records when models start and stop the simulation.
+    #[derive(Default)]
+    struct Lifecycle(Mutex<Vec<String>>);
+
+    impl SimObserver for Lifecycle {
+        fn on_start(&self, path: &str, t: f64) {
+            self.0.lock().unwrap().push(format!("{t} start {path}"));
+        }
+
+        fn on_stop(&self, path: &str, t: f64) {
+            self.0.lock().unwrap().push(format!("{t} stop {path}"));
+        }
+    }
+
+    /// Atomic model that modifies the structure of its parent coupled model.
+    /// At time 1, it adds a new generator. At time 5, it removes the original generator.
+    struct Executive {
+        component: Component,
+        handle: StructureHandle,
+        sigma: f64,
+        phase: usize,
+    }
+
+    impl HasComponent for Executive {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Executive {
+        fn lambda(&self, _output: &OutputCtx) {}
+
+        fn delta_int(&mut self) {
+            self.phase += 1;
+            match self.phase {
+                1 => {
+                    self.handle
+                        .add_component(Box::new(Generator::new("generator2", 2.5)));
+                    self.handle.add_eoc("generator2", "output", "output");
+                    self.sigma = 4.;
+                }
+                _ => {
+                    self.handle.remove_component("generator");
+                    // invalid changes are discarded
+                    self.handle.add_eoc("generator", "output", "output");
+                    self.handle
+                        .remove_ic("generator2", "output", "executive", "input");
+                    self.sigma = f64::INFINITY;
+                }
+            }
+        }
+
+        fn delta_ext(&mut self, e: f64, _input: &InputCtx) {
+            self.sigma -= e;
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    #[test]
+    fn test_dynamic_structure() {
+        let mut coupled = create_model();
+        let executive = Executive {
+            component: Component::new("executive"),
+            handle: coupled.structure_handle(),
+            sigma: 1.,
+            phase: 0,
+        };
+        coupled.add_component(Box::new(executive));
+        let recorder = Arc::new(Lifecycle::default());
+        // The executive has no couplings, as it only modifies the structure of the model
+        let mut simulator = RootCoordinator::unvalidated(coupled);
+        simulator.set_observer(recorder.clone());
+        simulator.collect_output::<usize>("output");
+        simulator.start();
+        let err = simulator.try_simulate_until(10.).unwrap_err();
+        let missing = Error::ComponentNotFound {
+            coupled: "root".to_string(),
+            component: "generator".to_string(),
+        };
+        assert_eq!(missing, err);
+        assert_eq!(5., simulator.get_time());
+        simulator.simulate_until(10.);
+        let expected = [(2., 0), (3.5, 0), (4., 1), (6., 1), (8.5, 2)];
+        assert_eq!(&expected, simulator.get_output::<usize>("output").unwrap());
+        assert_eq!(2, simulator.n_components());
+        assert_eq!(1, simulator.n_eocs());
+        assert_eq!(0, simulator.n_eics());
+        let events = recorder.0.lock().unwrap();
+        assert!(events.contains(&"1 start root.generator2".to_string()));
+        assert!(events.contains(&"5 stop root.generator".to_string()));
+        drop(events);
+
+        // structure can also be modified directly between simulation steps
+        simulator.remove_eoc("generator2", "output", "output");
+        simulator.simulate_until(20.);
+        assert_eq!(5, simulator.get_output::<usize>("output").unwrap().len());
+        let generator = simulator.remove_component("generator2");
+        assert_eq!("generator2", generator.get_name());
+        assert_eq!(1, simulator.n_components());
+    }
+
+    #[test]
+    fn test_invalid_changes() {
+        let mut coupled = create_model();
+        let handle = coupled.structure_handle();
+        handle.add_component(Box::new(Generator::new("generator", 1.)));
+        handle.add_eoc("generator", "input", "output");
+        handle.add_eic("input", "generator", "output");
+        handle.add_component(Box::new(Generator::new("generator2", 1.)));
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.start();
+        // the first invalid change is reported, but valid changes are still applied
+        let err = simulator.try_step().unwrap_err();
+        let duplicate = Error::DuplicateComponent {
+            coupled: "root".to_string(),
+            component: "generator".to_string(),
+        };
+        assert_eq!(duplicate, err);
+        assert_eq!(2, simulator.n_components());
+        assert_eq!(1, simulator.n_eocs());
+        assert_eq!(1, simulator.n_eics());
+        // errors are only reported once
+        simulator.try_step().unwrap();
+    }
+}
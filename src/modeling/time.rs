@@ -0,0 +1,286 @@
+use crate::DynRef;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display};
+use std::ops::{Add, Sub};
+
+/// Helper trait for adding serialization constraints to [`Time`] only when the `serde` feature is activated.
+#[cfg(not(feature = "serde"))]
+pub trait MaybeSerde {}
+/// Helper trait for adding serialization constraints to [`Time`] only when the `serde` feature is activated.
+#[cfg(feature = "serde")]
+pub trait MaybeSerde: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> MaybeSerde for T {}
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> MaybeSerde for T {}
+
+/// Interface for simulation time types.
+/// Models can use exact time arithmetic (e.g., integer ticks or [`Fixed`] point numbers)
+/// to avoid rounding errors when comparing simulation times.
+///
+/// Implementations are provided for [`f64`], [`u64`], [`i64`], and [`Fixed`].
+/// Integer types use their maximum value to represent infinity.
+pub trait Time: Copy + PartialOrd + Debug + Display + DynRef + MaybeSerde {
+    /// Initial simulation time.
+    const ZERO: Self;
+
+    /// Infinite simulation time. It is used for passive models.
+    const INFINITY: Self;
+
+    /// Returns the time after advancing `delta` from `self`.
+    /// If any of the operands is infinity, it returns infinity.
+    fn advance(self, delta: Self) -> Self;
+
+    /// Returns the time elapsed from `t` up to `self`.
+    fn elapsed_since(self, t: Self) -> Self;
+
+    /// Total ordering between simulation times.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Time for f64 {
+    const ZERO: Self = 0.;
+    const INFINITY: Self = f64::INFINITY;
+
+    #[inline]
+    fn advance(self, delta: Self) -> Self {
+        self + delta
+    }
+
+    #[inline]
+    fn elapsed_since(self, t: Self) -> Self {
+        self - t
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Implements the [`Time`] trait for integer types.
+macro_rules! impl_integer_time {
+    ($($t:ty),*) => {$(
+        impl Time for $t {
+            const ZERO: Self = 0;
+            const INFINITY: Self = <$t>::MAX;
+
+            #[inline]
+            fn advance(self, delta: Self) -> Self {
+                if self == Self::INFINITY || delta == Self::INFINITY {
+                    return Self::INFINITY;
+                }
+                self.saturating_add(delta)
+            }
+
+            #[inline]
+            fn elapsed_since(self, t: Self) -> Self {
+                self - t
+            }
+
+            #[inline]
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+        }
+    )*};
+}
+
+impl_integer_time!(u64, i64);
+
+/// Fixed-point simulation time with `DECIMALS` decimal digits.
+/// For instance, `Fixed<3>` represents times with a resolution of one millisecond.
+/// Additions and subtractions are exact. The maximum value represents infinity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Fixed<const DECIMALS: u32>(i64);
+
+impl<const DECIMALS: u32> Fixed<DECIMALS> {
+    /// Number of units per second.
+    const SCALE: i64 = 10_i64.pow(DECIMALS);
+
+    /// Creates a new fixed-point time from its raw value (i.e., the number of time units).
+    #[inline]
+    pub const fn from_raw(raw: i64) -> Self {
+        Self(raw)
+    }
+
+    /// Creates a new fixed-point time from a floating-point number, rounding to the nearest unit.
+    #[inline]
+    pub fn from_f64(t: f64) -> Self {
+        match t.is_infinite() && t > 0. {
+            true => Self(i64::MAX),
+            false => Self((t * Self::SCALE as f64).round() as i64),
+        }
+    }
+
+    /// Returns the raw value (i.e., the number of time units) of the fixed-point time.
+    #[inline]
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+
+    /// Returns the fixed-point time as a floating-point number.
+    #[inline]
+    pub fn to_f64(self) -> f64 {
+        match self.0 {
+            i64::MAX => f64::INFINITY,
+            raw => raw as f64 / Self::SCALE as f64,
+        }
+    }
+}
+
+impl<const DECIMALS: u32> Add for Fixed<DECIMALS> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        self.advance(rhs)
+    }
+}
+
+impl<const DECIMALS: u32> Sub for Fixed<DECIMALS> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.elapsed_since(rhs)
+    }
+}
+
+impl<const DECIMALS: u32> Display for Fixed<DECIMALS> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 == i64::MAX {
+            return write!(f, "inf");
+        }
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let (int, frac) = (
+            self.0.unsigned_abs() / Self::SCALE as u64,
+            self.0.unsigned_abs() % Self::SCALE as u64,
+        );
+        match DECIMALS {
+            0 => write!(f, "{sign}{int}"),
+            _ => write!(f, "{sign}{int}.{frac:0width$}", width = DECIMALS as usize),
+        }
+    }
+}
+
+impl<const DECIMALS: u32> Time for Fixed<DECIMALS> {
+    const ZERO: Self = Self(0);
+    const INFINITY: Self = Self(i64::MAX);
+
+    #[inline]
+    fn advance(self, delta: Self) -> Self {
+        Self(self.0.advance(delta.0))
+    }
+
+    #[inline]
+    fn elapsed_since(self, t: Self) -> Self {
+        Self(self.0 - t.0)
+    }
+
+    #[inline]
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, Coupled, HasComponent, InputCtx, OutPort, OutputCtx};
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_integer_time() {
+        assert_eq!(5, 2_u64.advance(3));
+        assert_eq!(u64::INFINITY, 2_u64.advance(u64::INFINITY));
+        assert_eq!(i64::INFINITY, (-2_i64).advance(i64::INFINITY));
+        assert_eq!(i64::INFINITY, i64::INFINITY.advance(-2));
+        assert_eq!(3, 5_i64.elapsed_since(2));
+    }
+
+    #[test]
+    fn test_fixed_time() {
+        let t = Fixed::<3>::from_f64(0.1);
+        let mut acc = Fixed::ZERO;
+        for _ in 0..10 {
+            acc = acc + t;
+        }
+        assert_eq!(Fixed::from_raw(1000), acc);
+        assert_eq!(1., acc.to_f64());
+        assert_eq!("1.000", acc.to_string());
+        assert_eq!("-0.250", Fixed::<3>::from_f64(-0.25).to_string());
+        assert_eq!("inf", Fixed::<3>::INFINITY.to_string());
+        assert_eq!(Fixed::<3>::INFINITY, Fixed::from_f64(f64::INFINITY));
+        assert_eq!(Fixed::<3>::INFINITY, acc.advance(Fixed::INFINITY));
+        assert_eq!(f64::INFINITY, Fixed::<2>::INFINITY.to_f64());
+    }
+
+    /// Atomic model with integer simulation times that periodically sends its number of ticks.
+    struct Ticker {
+        clock: Component<u64>,
+        ticks: u64,
+        ticks_out: OutPort<u64>,
+    }
+
+    impl Ticker {
+        fn new(name: &str) -> Self {
+            let mut clock = Component::new(name);
+            let ticks_out = clock.add_out_port("output");
+            Self {
+                clock,
+                ticks: 0,
+                ticks_out,
+            }
+        }
+    }
+
+    impl HasComponent for Ticker {
+        type Time = u64;
+
+        fn get_component(&self) -> &Component<u64> {
+            &self.clock
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component<u64> {
+            &mut self.clock
+        }
+    }
+
+    impl Atomic for Ticker {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.ticks_out, self.ticks);
+        }
+
+        fn delta_int(&mut self) {
+            self.ticks += 1;
+        }
+
+        fn delta_ext(&mut self, _e: u64, _input: &InputCtx) {}
+
+        fn ta(&self) -> u64 {
+            match self.ticks {
+                3 => u64::INFINITY,
+                _ => 3,
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_time_simulation() {
+        let ticker = Ticker::new("ticker");
+        let mut coupled = Coupled::new("root");
+        coupled.add_out_port::<u64>("output");
+        coupled.add_component(Box::new(ticker));
+        coupled.add_eoc("ticker", "output", "output");
+
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<u64>("output");
+        simulator.simulate(u64::INFINITY);
+        let expected = [(3, 0), (6, 1), (9, 2)];
+        assert_eq!(&expected, simulator.get_output::<u64>("output").unwrap());
+    }
+}
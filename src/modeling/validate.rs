@@ -0,0 +1,502 @@
+use super::coupled::port_address;
+use super::port::Port;
+use super::{Coupled, Time};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// Problem found when validating a DEVS model with [`Coupled::validate`].
+/// Models with issues can still be simulated, but they are likely to produce wrong results.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Issue {
+    /// Input port that does not receive messages through any coupling.
+    UnconnectedInPort { path: String, port: String },
+    /// Output port that does not send messages through any coupling.
+    UnconnectedOutPort { path: String, port: String },
+    /// Component without any coupling.
+    IsolatedComponent { path: String },
+    /// Component with the same name as one of its ancestors.
+    DuplicateName { path: String },
+    /// Full path shared by several components (e.g., due to names with dots).
+    DuplicatePath { path: String },
+    /// Cycle of couplings between atomic models that may send messages in zero time
+    /// (see [`super::Atomic::may_emit_in_zero_time`]). The simulation may never advance.
+    AlgebraicLoop { paths: Vec<String> },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnconnectedInPort { path, port } => {
+                write!(f, "input port {path}.{port} is not connected")
+            }
+            Self::UnconnectedOutPort { path, port } => {
+                write!(f, "output port {path}.{port} is not connected")
+            }
+            Self::IsolatedComponent { path } => write!(f, "component {path} has no couplings"),
+            Self::DuplicateName { path } => {
+                write!(
+                    f,
+                    "component {path} has the same name as one of its ancestors"
+                )
+            }
+            Self::DuplicatePath { path } => write!(f, "several components have path {path}"),
+            Self::AlgebraicLoop { paths } => {
+                write!(f, "algebraic loop between components {}", paths.join(", "))
+            }
+        }
+    }
+}
+
+/// Auxiliary state for validating a hierarchy of DEVS models.
+#[derive(Default)]
+struct Validator {
+    /// Issues found so far.
+    issues: Vec<Issue>,
+    /// Set of issues found so far. It is used for avoiding duplicate issues.
+    reported: HashSet<Issue>,
+    /// Full paths of all the components found so far.
+    paths: HashSet<String>,
+    /// Couplings between ports, from the address of the sending port to the addresses of the receiving ports.
+    links: HashMap<*const (), Vec<*const ()>>,
+    /// Full path and addresses of the input and output ports of the atomic models
+    /// that may send messages in zero time.
+    zero_time: Vec<(String, Vec<*const ()>, Vec<*const ()>)>,
+}
+
+impl Validator {
+    /// Adds a new issue, unless it was already found.
+    fn report(&mut self, issue: Issue) {
+        if self.reported.insert(issue.clone()) {
+            self.issues.push(issue);
+        }
+    }
+
+    /// Validates a coupled model with the given full path and all its subcomponents.
+    fn visit<T: Time>(&mut self, coupled: &Coupled<T>, path: &str, ancestors: &mut Vec<String>) {
+        // Names of the ports of each component (or the coupled model itself) used by couplings
+        let mut from_ports: HashMap<Option<&str>, HashSet<&str>> = HashMap::new();
+        let mut to_ports: HashMap<Option<&str>, HashSet<&str>> = HashMap::new();
+        for coupling in coupled.eics.iter().chain(&coupled.ics).chain(&coupled.eocs) {
+            let info = coupling.info();
+            let from = from_ports.entry(info.component_from).or_default();
+            from.insert(info.port_from);
+            let to = to_ports.entry(info.component_to).or_default();
+            to.insert(info.port_to);
+            let (from, to) = coupling.port_addresses();
+            self.links.entry(from).or_default().push(to);
+        }
+        let is_used = |ports: &HashMap<Option<&str>, HashSet<&str>>, component, port: &str| {
+            ports
+                .get(&component)
+                .is_some_and(|ports| ports.contains(port))
+        };
+        // Ports of the coupled model must be connected to its subcomponents
+        for (port, _) in coupled.component.get_in_ports() {
+            if !is_used(&from_ports, None, &port) {
+                let path = path.to_string();
+                self.report(Issue::UnconnectedInPort { path, port });
+            }
+        }
+        for (port, _) in coupled.component.get_out_ports() {
+            if !is_used(&to_ports, None, &port) {
+                let path = path.to_string();
+                self.report(Issue::UnconnectedOutPort { path, port });
+            }
+        }
+
+        ancestors.push(coupled.component.get_name().to_string());
+        for component in coupled.components.iter() {
+            let name = component.get_name();
+            let child_path = format!("{path}.{name}");
+            if ancestors.iter().any(|ancestor| ancestor == name) {
+                let path = child_path.clone();
+                self.report(Issue::DuplicateName { path });
+            }
+            if !self.paths.insert(child_path.clone()) {
+                let path = child_path.clone();
+                self.report(Issue::DuplicatePath { path });
+            }
+            let key = Some(name);
+            if !from_ports.contains_key(&key) && !to_ports.contains_key(&key) {
+                let path = child_path.clone();
+                self.report(Issue::IsolatedComponent { path });
+            } else {
+                for (port, _) in component.get_component().get_in_ports() {
+                    if !is_used(&to_ports, key, &port) {
+                        let path = child_path.clone();
+                        self.report(Issue::UnconnectedInPort { path, port });
+                    }
+                }
+                for (port, _) in component.get_component().get_out_ports() {
+                    if !is_used(&from_ports, key, &port) {
+                        let path = child_path.clone();
+                        self.report(Issue::UnconnectedOutPort { path, port });
+                    }
+                }
+            }
+            match component.as_coupled() {
+                Some(coupled) => self.visit(coupled, &child_path, ancestors),
+                None if component.may_emit_in_zero_time() => {
+                    let addresses = |ports: Vec<(String, Arc<dyn Port>)>| {
+                        ports.iter().map(|(_, port)| port_address(port)).collect()
+                    };
+                    let in_ports = addresses(component.get_component().get_in_ports());
+                    let out_ports = addresses(component.get_component().get_out_ports());
+                    self.zero_time.push((child_path, in_ports, out_ports));
+                }
+                None => {}
+            }
+        }
+        ancestors.pop();
+    }
+
+    /// Looks for cycles of couplings between atomic models that may send messages in zero time.
+    fn find_algebraic_loops(&mut self) {
+        let mut in_ports = HashMap::new();
+        for (i, (_, ports, _)) in self.zero_time.iter().enumerate() {
+            in_ports.extend(ports.iter().map(|&port| (port, i)));
+        }
+        // Atomic-to-atomic graph, resolving the chains of couplings through coupled models
+        let mut graph = vec![Vec::new(); self.zero_time.len()];
+        for (i, (_, _, out_ports)) in self.zero_time.iter().enumerate() {
+            let mut visited = HashSet::new();
+            let mut stack: Vec<_> = out_ports
+                .iter()
+                .filter_map(|port| self.links.get(port))
+                .flatten()
+                .collect();
+            while let Some(port) = stack.pop() {
+                if !visited.insert(port) {
+                    continue;
+                }
+                match self.links.get(port) {
+                    Some(ports) => stack.extend(ports),
+                    None => {
+                        if let Some(&j) = in_ports.get(port) {
+                            if !graph[i].contains(&j) {
+                                graph[i].push(j);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let loops: Vec<_> = strongly_connected_components(&graph)
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || graph[scc[0]].contains(&scc[0]))
+            .map(|mut scc| {
+                scc.sort_unstable();
+                scc.into_iter()
+                    .map(|i| self.zero_time[i].0.clone())
+                    .collect()
+            })
+            .collect();
+        for paths in loops {
+            self.report(Issue::AlgebraicLoop { paths });
+        }
+    }
+}
+
+/// Returns the strongly connected components of a directed graph (Tarjan's algorithm).
+fn strongly_connected_components(graph: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    /// State of Tarjan's algorithm.
+    struct Tarjan<'a> {
+        graph: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        sccs: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        /// Visits all the vertices reachable from `root`.
+        /// It uses an explicit stack of (vertex, next edge) pairs instead of recursion,
+        /// so large graphs do not overflow the call stack.
+        fn connect(&mut self, root: usize) {
+            self.visit(root);
+            let mut calls = vec![(root, 0)];
+            while let Some((v, edge)) = calls.pop() {
+                if let Some(&w) = self.graph[v].get(edge) {
+                    calls.push((v, edge + 1));
+                    match self.index[w] {
+                        None => {
+                            self.visit(w);
+                            calls.push((w, 0));
+                        }
+                        Some(index) if self.on_stack[w] => {
+                            self.low_link[v] = self.low_link[v].min(index);
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                // All the successors of v have been visited
+                if let Some(&(parent, _)) = calls.last() {
+                    self.low_link[parent] = self.low_link[parent].min(self.low_link[v]);
+                }
+                if Some(self.low_link[v]) == self.index[v] {
+                    let mut scc = Vec::new();
+                    loop {
+                        let w = self.stack.pop().unwrap();
+                        self.on_stack[w] = false;
+                        scc.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    self.sccs.push(scc);
+                }
+            }
+        }
+
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next_index);
+            self.low_link[v] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+        }
+    }
+
+    let n = graph.len();
+    let mut tarjan = Tarjan {
+        graph,
+        index: vec![None; n],
+        low_link: vec![0; n],
+        on_stack: vec![false; n],
+        stack: Vec::new(),
+        next_index: 0,
+        sccs: Vec::new(),
+    };
+    for v in 0..n {
+        if tarjan.index[v].is_none() {
+            tarjan.connect(v);
+        }
+    }
+    tarjan.sccs
+}
+
+impl<T: Time> Coupled<T> {
+    /// Looks for common modeling mistakes in the coupled model and all its subcomponents:
+    ///
+    /// - input ports that do not receive messages through any coupling.
+    /// - output ports that do not send messages through any coupling.
+    /// - components without any coupling.
+    /// - components with the same name as one of their ancestors, or with the same full path.
+    /// - cycles of couplings between atomic models that may send messages in zero time
+    ///   (see [`super::Atomic::may_emit_in_zero_time`]).
+    ///
+    /// Components are identified by their full path (e.g., `root.subcoupled.atomic`).
+    /// It returns an empty vector if no issue is found.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut validator = Validator::default();
+        let path = self.component.get_name();
+        validator.visit(self, path, &mut Vec::new());
+        validator.find_algebraic_loops();
+        validator.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, HasComponent, InPort, InputCtx, OutPort, OutputCtx};
+
+    /// Atomic model that forwards the messages it receives after a delay.
+    /// A zero delay makes it a candidate for algebraic loops.
+    struct Relay {
+        component: Component,
+        delay: f64,
+        buffer: Vec<usize>,
+        input: InPort<usize>,
+        output: OutPort<usize>,
+    }
+
+    impl Relay {
+        fn new(name: &str) -> Self {
+            Self::with_delay(name, 0.)
+        }
+
+        fn with_delay(name: &str, delay: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                delay,
+                buffer: Vec::new(),
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Relay {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Relay {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_values(&self.output, &self.buffer);
+        }
+
+        fn delta_int(&mut self) {
+            self.buffer.clear();
+        }
+
+        fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+            self.buffer.extend_from_slice(input.get_values(&self.input));
+        }
+
+        fn ta(&self) -> f64 {
+            match self.buffer.is_empty() {
+                true => f64::INFINITY,
+                false => self.delay,
+            }
+        }
+
+        fn may_emit_in_zero_time(&self) -> bool {
+            self.delay == 0.
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Relay::new("relay")));
+        coupled.add_eic("input", "relay", "input");
+        coupled.add_eoc("relay", "output", "output");
+        assert!(coupled.validate().is_empty());
+
+        let mut sub = Coupled::new("sub");
+        sub.add_in_port::<usize>("input");
+        sub.add_out_port::<usize>("output");
+        sub.add_component(Box::new(Relay::new("relay")));
+        sub.add_component(Box::new(Relay::new("root")));
+        sub.add_eic("input", "relay", "input");
+        sub.add_eoc("relay", "output", "output");
+
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<usize>("input");
+        coupled.add_component(Box::new(Relay::new("relay")));
+        coupled.add_component(Box::new(sub));
+        coupled.add_ic("relay", "output", "sub", "input");
+        coupled.add_ic("sub", "output", "relay", "input");
+
+        let issues = coupled.validate();
+        let expected = [
+            Issue::UnconnectedInPort {
+                path: "root".to_string(),
+                port: "input".to_string(),
+            },
+            Issue::DuplicateName {
+                path: "root.sub.root".to_string(),
+            },
+            Issue::IsolatedComponent {
+                path: "root.sub.root".to_string(),
+            },
+            Issue::AlgebraicLoop {
+                paths: vec!["root.relay".to_string(), "root.sub.relay".to_string()],
+            },
+        ];
+        assert_eq!(&expected, issues.as_slice());
+        assert_eq!(
+            "algebraic loop between components root.relay, root.sub.relay",
+            issues[3].to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_unconnected_ports() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Relay::new("a")));
+        coupled.add_component(Box::new(Relay::new("b")));
+        coupled.add_ic("a", "output", "b", "input");
+
+        let expected = [
+            Issue::UnconnectedOutPort {
+                path: "root".to_string(),
+                port: "output".to_string(),
+            },
+            Issue::UnconnectedInPort {
+                path: "root.a".to_string(),
+                port: "input".to_string(),
+            },
+            Issue::UnconnectedOutPort {
+                path: "root.b".to_string(),
+                port: "output".to_string(),
+            },
+        ];
+        assert_eq!(&expected, coupled.validate().as_slice());
+        assert_eq!(
+            "output port root.b.output is not connected",
+            expected[2].to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_duplicate_path() {
+        let mut sub = Coupled::new("a");
+        sub.add_component(Box::new(Relay::new("b")));
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(sub));
+        coupled.add_component(Box::new(Relay::new("a.b")));
+
+        let issues = coupled.validate();
+        let expected = Issue::DuplicatePath {
+            path: "root.a.b".to_string(),
+        };
+        assert!(issues.contains(&expected));
+        assert_eq!(1, issues.iter().filter(|issue| **issue == expected).count());
+    }
+
+    #[test]
+    fn test_validate_algebraic_loops() {
+        // a self-loop of a zero-time model is an algebraic loop
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Relay::new("relay")));
+        coupled.add_ic("relay", "output", "relay", "input");
+        let expected = [Issue::AlgebraicLoop {
+            paths: vec!["root.relay".to_string()],
+        }];
+        assert_eq!(&expected, coupled.validate().as_slice());
+
+        // a loop is broken by any model that does not send messages in zero time
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Relay::new("a")));
+        coupled.add_component(Box::new(Relay::with_delay("b", 1.)));
+        coupled.add_ic("a", "output", "b", "input");
+        coupled.add_ic("b", "output", "a", "input");
+        assert!(coupled.validate().is_empty());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // 0 -> 1 -> 2 -> 0, 2 -> 3, 3 -> 3, 4
+        let graph = [vec![1], vec![2], vec![0, 3], vec![3], vec![]];
+        let mut sccs = strongly_connected_components(&graph);
+        sccs.iter_mut().for_each(|scc| scc.sort_unstable());
+        assert_eq!(vec![vec![3], vec![0, 1, 2], vec![4]], sccs);
+
+        // long chains of couplings do not overflow the stack
+        let n = 1_000_000;
+        let graph: Vec<_> = (0..n).map(|i| vec![(i + 1) % n]).collect();
+        let sccs = strongly_connected_components(&graph);
+        assert_eq!(1, sccs.len());
+        assert_eq!(n, sccs[0].len());
+    }
+}
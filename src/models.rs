@@ -0,0 +1,135 @@
+mod counter;
+mod delay;
+mod generator;
+mod merge;
+mod queue;
+mod router;
+mod server;
+mod sink;
+
+pub use counter::Counter;
+pub use delay::Delay;
+pub use generator::Generator;
+pub use merge::{Merge, Split};
+pub use queue::Queue;
+pub use router::{Router, Switch};
+pub use server::Server;
+pub use sink::{Collected, Sink};
+
+use crate::DynRef;
+use std::fmt::Debug;
+
+/// Helper trait for the messages of the models of this module.
+/// The ports of these models are traced, so messages must implement [`Debug`].
+pub trait Message: DynRef + Clone + Debug {}
+
+impl<T: DynRef + Clone + Debug> Message for T {}
+
+/// Implements [`HasComponent`](crate::modeling::HasComponent) for a model with a `component` field.
+macro_rules! impl_has_component {
+    ($model:ident) => {
+        impl<T: $crate::models::Message> $crate::modeling::HasComponent for $model<T> {
+            type Time = f64;
+
+            #[inline]
+            fn get_component(&self) -> &$crate::modeling::Component {
+                &self.component
+            }
+
+            #[inline]
+            fn get_component_mut(&mut self) -> &mut $crate::modeling::Component {
+                &mut self.component
+            }
+        }
+    };
+}
+use impl_has_component;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::Coupled;
+    use crate::simulation::RootCoordinator;
+
+    /// Returns a function that returns 0, 1, 2, etc.
+    fn sequence() -> impl FnMut() -> usize + Send + Sync {
+        let mut count = 0;
+        move || {
+            count += 1;
+            count - 1
+        }
+    }
+
+    #[test]
+    fn test_queue_server() {
+        let simulate = |queue: Queue<usize>| {
+            let sink = Sink::<usize>::new("sink");
+            let collected = sink.collected();
+            let mut coupled = Coupled::new("root");
+            coupled.add_component(Box::new(Generator::periodic("generator", 1., sequence())));
+            coupled.add_component(Box::new(queue));
+            coupled.add_component(Box::new(Server::new("server", |_: &usize| 2.5)));
+            coupled.add_component(Box::new(sink));
+            coupled.add_ic("generator", "output", "queue", "input");
+            coupled.add_ic("queue", "output", "server", "input");
+            coupled.add_ic("server", "ready", "queue", "ready");
+            coupled.add_ic("server", "output", "sink", "input");
+            assert!(coupled.validate().is_empty());
+            RootCoordinator::new(coupled).simulate(10.5);
+            let collected = collected.lock().unwrap().clone();
+            collected
+        };
+
+        let expected = vec![(3.5, 0), (6., 1), (8.5, 2)];
+        assert_eq!(expected, simulate(Queue::new("queue")));
+        // Messages with greater values are processed first
+        let expected = vec![(3.5, 0), (6., 2), (8.5, 5)];
+        let queue = Queue::priority("queue", |a: &usize, b: &usize| b.cmp(a));
+        assert_eq!(expected, simulate(queue));
+    }
+
+    #[test]
+    fn test_routing() {
+        let sinks = ["merged", "counted", "switched_0", "switched_1"].map(Sink::<usize>::new);
+        let collected = sinks.each_ref().map(|sink| sink.collected());
+
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(Generator::periodic("generator", 1., sequence())));
+        coupled.add_component(Box::new(Split::<usize>::new("split", 2)));
+        coupled.add_component(Box::new(Router::new("router", 2, |x: &usize| Some(x % 2))));
+        coupled.add_component(Box::new(Delay::<usize>::new("delay", 0.5)));
+        coupled.add_component(Box::new(Merge::<usize>::new("merge", 2)));
+        coupled.add_component(Box::new(Counter::<usize>::new("counter")));
+        coupled.add_component(Box::new(Switch::<usize>::new("switch", 2)));
+        for sink in sinks {
+            coupled.add_component(Box::new(sink));
+        }
+        // Even numbers are merged immediately, and odd numbers after a delay
+        coupled.add_ic("generator", "output", "split", "input");
+        coupled.add_ic("split", "output_0", "router", "input");
+        coupled.add_ic("router", "output_0", "merge", "input_0");
+        coupled.add_ic("router", "output_1", "delay", "input");
+        coupled.add_ic("delay", "output", "merge", "input_1");
+        coupled.add_ic("merge", "output", "merged", "input");
+        // The switch changes its output every time a message is counted
+        coupled.add_ic("split", "output_1", "counter", "input");
+        coupled.add_ic("counter", "count", "counted", "input");
+        coupled.add_ic_map("counter", "count", "switch", "select", |c: &usize| {
+            Some(c % 2)
+        });
+        coupled.add_ic("generator", "output", "switch", "input");
+        coupled.add_ic("switch", "output_0", "switched_0", "input");
+        coupled.add_ic("switch", "output_1", "switched_1", "input");
+        RootCoordinator::new(coupled).simulate(4.9);
+
+        let expected = [
+            vec![(1., 0), (2.5, 1), (3., 2), (4.5, 3)],
+            vec![(1., 1), (2., 2), (3., 3), (4., 4)],
+            vec![(1., 0), (3., 2)],
+            vec![(2., 1), (4., 3)],
+        ];
+        for (expected, collected) in expected.iter().zip(collected) {
+            assert_eq!(expected, &*collected.lock().unwrap());
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+
+/// Atomic model that counts the messages it receives through its `input` port.
+/// Every time it receives messages, it immediately sends the total count through its `count` port.
+pub struct Counter<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    output: OutPort<usize>,
+    /// Number of messages received so far.
+    count: usize,
+    /// Whether the count must be sent or not.
+    is_pending: bool,
+}
+
+impl<T: Message> Counter<T> {
+    /// Creates a new counter.
+    pub fn new(name: &str) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let output = component.add_traced_out_port("count");
+        Self {
+            component,
+            input,
+            output,
+            count: 0,
+            is_pending: false,
+        }
+    }
+
+    /// Returns the number of messages received so far.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl_has_component!(Counter);
+
+impl<T: Message> Atomic for Counter<T> {
+    fn start(&mut self) {
+        self.count = 0;
+        self.is_pending = false;
+    }
+
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, self.count);
+    }
+
+    fn delta_int(&mut self) {
+        self.is_pending = false;
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        self.count += input.get_values(&self.input).len();
+        self.is_pending = true;
+    }
+
+    fn ta(&self) -> f64 {
+        match self.is_pending {
+            true => 0.,
+            false => f64::INFINITY,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_counter() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<char>("input");
+        coupled.add_out_port::<usize>("count");
+        coupled.add_component(Box::new(Counter::<char>::new("counter")));
+        coupled.add_eic("input", "counter", "input");
+        coupled.add_eoc("counter", "count", "count");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<usize>("count");
+        // The count is sent once for all the messages received at the same time
+        simulator.start();
+        simulator.inject("input", &['a', 'b'], 1.);
+        simulator.inject("input", &['c'], 2.);
+        simulator.simulate_until(3.);
+        assert_eq!(
+            &[(1., 2), (2., 3)],
+            simulator.get_output::<usize>("count").unwrap()
+        );
+        // The count is reset when the simulation starts again
+        simulator.start();
+        simulator.inject("input", &['d'], 1.);
+        simulator.simulate_until(2.);
+        let output = simulator.get_output::<usize>("count").unwrap();
+        assert_eq!(Some(&(1., 1)), output.last());
+    }
+}
@@ -0,0 +1,140 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+use std::collections::VecDeque;
+
+/// Atomic model that sends every message it receives after a fixed delay.
+///
+/// Messages are received through the `input` port and sent through the `output` port
+/// in arrival order. Messages received while others are delayed are not discarded.
+pub struct Delay<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    output: OutPort<T>,
+    /// Delay of the messages.
+    delay: f64,
+    /// Time elapsed since the simulation started.
+    clock: f64,
+    /// Delayed messages, together with the time at which they are sent.
+    messages: VecDeque<(f64, T)>,
+}
+
+impl<T: Message> Delay<T> {
+    /// Creates a new delay. This method panics if the delay is negative.
+    pub fn new(name: &str, delay: f64) -> Self {
+        if delay < 0. {
+            panic!("delay must not be negative");
+        }
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let output = component.add_traced_out_port("output");
+        Self {
+            component,
+            input,
+            output,
+            delay,
+            clock: 0.,
+            messages: VecDeque::new(),
+        }
+    }
+
+    /// Returns the time at which the next delayed messages are sent (if any).
+    #[inline]
+    fn t_next(&self) -> Option<f64> {
+        self.messages.front().map(|(t, _)| *t)
+    }
+}
+
+impl_has_component!(Delay);
+
+impl<T: Message> Atomic for Delay<T> {
+    fn start(&mut self) {
+        self.clock = 0.;
+        self.messages.clear();
+    }
+
+    fn lambda(&self, output: &OutputCtx) {
+        let t_next = self.t_next();
+        for (_, message) in self.messages.iter().take_while(|(t, _)| Some(*t) == t_next) {
+            output.add_value(&self.output, message.clone());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        if let Some(t_next) = self.t_next() {
+            self.clock = t_next;
+            while self.t_next() == Some(t_next) {
+                self.messages.pop_front();
+            }
+        }
+    }
+
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+        self.clock += e;
+        let t = self.clock + self.delay;
+        for message in input.get_values(&self.input) {
+            self.messages.push_back((t, message.clone()));
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.t_next()
+            .map_or(f64::INFINITY, |t| (t - self.clock).max(0.))
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        self.delay == 0.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    /// Returns a simulator of a coupled model with the delay connected to its ports.
+    fn create_simulator(delay: f64) -> RootCoordinator<Coupled> {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<char>("input");
+        coupled.add_out_port::<char>("output");
+        coupled.add_component(Box::new(Delay::<char>::new("delay", delay)));
+        coupled.add_eic("input", "delay", "input");
+        coupled.add_eoc("delay", "output", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<char>("output");
+        simulator.start();
+        simulator
+    }
+
+    #[test]
+    fn test_delay_pending() {
+        let mut simulator = create_simulator(2.);
+        simulator.inject("input", &['a'], 1.);
+        // Messages received while others are delayed are kept in arrival order
+        simulator.inject("input", &['b', 'c'], 2.);
+        simulator.inject("input", &['d'], 2.5);
+        // Messages received when others are sent are delayed too
+        simulator.inject("input", &['e'], 3.);
+        simulator.simulate_until(10.);
+        let expected = [(3., 'a'), (4., 'b'), (4., 'c'), (4.5, 'd'), (5., 'e')];
+        assert_eq!(&expected, simulator.get_output("output").unwrap());
+    }
+
+    #[test]
+    fn test_zero_delay() {
+        let mut simulator = create_simulator(0.);
+        simulator.inject("input", &['a'], 1.);
+        simulator.inject("input", &['b'], 1.);
+        simulator.simulate_until(2.);
+        assert_eq!(
+            &[(1., 'a'), (1., 'b')],
+            simulator.get_output("output").unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "delay must not be negative")]
+    fn test_negative_delay() {
+        Delay::<char>::new("delay", -1.);
+    }
+}
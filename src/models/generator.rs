@@ -0,0 +1,139 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+
+/// Atomic model that generates messages. The time between messages is given by a function,
+/// so generators can be periodic or stochastic.
+///
+/// Messages are sent through the `output` port. The first message is sent when
+/// the first inter-arrival time has elapsed since the start of the simulation.
+pub struct Generator<T: Message> {
+    component: Component,
+    output: OutPort<T>,
+    /// Time remaining until the next message.
+    sigma: f64,
+    /// Next message to be sent.
+    next: Option<T>,
+    /// Function that returns the time between two messages.
+    interarrival: Box<dyn FnMut() -> f64 + Send + Sync>,
+    /// Function that returns the next message to be sent.
+    message: Box<dyn FnMut() -> T + Send + Sync>,
+}
+
+impl<T: Message> Generator<T> {
+    /// Creates a new generator. `interarrival` returns the time between two messages
+    /// (e.g., a sample of an exponential distribution), and `message` returns the next message.
+    pub fn new(
+        name: &str,
+        interarrival: impl FnMut() -> f64 + Send + Sync + 'static,
+        message: impl FnMut() -> T + Send + Sync + 'static,
+    ) -> Self {
+        let mut component = Component::new(name);
+        let output = component.add_traced_out_port("output");
+        Self {
+            component,
+            output,
+            sigma: f64::INFINITY,
+            next: None,
+            interarrival: Box::new(interarrival),
+            message: Box::new(message),
+        }
+    }
+
+    /// Creates a new generator that sends a message every `period`.
+    pub fn periodic(
+        name: &str,
+        period: f64,
+        message: impl FnMut() -> T + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(name, move || period, message)
+    }
+
+    /// Prepares the next message and the time until it is sent.
+    fn schedule_next(&mut self) {
+        self.sigma = (self.interarrival)();
+        self.next = Some((self.message)());
+    }
+}
+
+impl_has_component!(Generator);
+
+impl<T: Message> Atomic for Generator<T> {
+    fn start(&mut self) {
+        self.schedule_next();
+    }
+
+    fn lambda(&self, output: &OutputCtx) {
+        if let Some(message) = &self.next {
+            output.add_value(&self.output, message.clone());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.schedule_next();
+    }
+
+    fn delta_ext(&mut self, e: f64, _input: &InputCtx) {
+        self.sigma -= e;
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    /// Returns a simulator of a coupled model with the generator connected to its output port.
+    fn create_simulator(generator: Generator<usize>) -> RootCoordinator<Coupled> {
+        let mut coupled = Coupled::new("root");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(generator));
+        coupled.add_eoc("generator", "output", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<usize>("output");
+        simulator
+    }
+
+    #[test]
+    fn test_interarrival() {
+        let mut times = vec![1., 0.5, 0., 2.].into_iter();
+        let mut count = 0;
+        let generator = Generator::new(
+            "generator",
+            move || times.next().unwrap_or(f64::INFINITY),
+            move || {
+                count += 1;
+                count
+            },
+        );
+        let mut simulator = create_simulator(generator);
+        simulator.simulate(4.);
+        let expected = [(1., 1), (1.5, 2), (1.5, 3), (3.5, 4)];
+        assert_eq!(&expected, simulator.get_output::<usize>("output").unwrap());
+    }
+
+    #[test]
+    fn test_infinite_interarrival() {
+        let generator = Generator::new("generator", || f64::INFINITY, || 0);
+        let mut simulator = create_simulator(generator);
+        simulator.start();
+        assert_eq!(f64::INFINITY, simulator.next_event_time());
+        simulator.simulate_until(100.);
+        assert!(simulator.get_output::<usize>("output").unwrap().is_empty());
+
+        // Generators stop after an infinite inter-arrival time
+        let mut times = vec![1., f64::INFINITY].into_iter();
+        let generator = Generator::new(
+            "generator",
+            move || times.next().unwrap_or(f64::INFINITY),
+            || 0,
+        );
+        let mut simulator = create_simulator(generator);
+        simulator.simulate(100.);
+        assert_eq!(&[(1., 0)], simulator.get_output::<usize>("output").unwrap());
+        assert_eq!(f64::INFINITY, simulator.next_event_time());
+    }
+}
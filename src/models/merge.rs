@@ -0,0 +1,147 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+
+/// Atomic model that forwards the messages it receives through any of its input ports
+/// (`input_0`, `input_1`, etc.) to its `output` port. Messages are forwarded immediately,
+/// in the order of the input ports.
+pub struct Merge<T: Message> {
+    component: Component,
+    inputs: Vec<InPort<T>>,
+    output: OutPort<T>,
+    /// Messages to be forwarded.
+    messages: Vec<T>,
+}
+
+impl<T: Message> Merge<T> {
+    /// Creates a new merge with `n_inputs` input ports.
+    pub fn new(name: &str, n_inputs: usize) -> Self {
+        let mut component = Component::new(name);
+        let inputs = (0..n_inputs)
+            .map(|i| component.add_traced_in_port(&format!("input_{i}")))
+            .collect();
+        let output = component.add_traced_out_port("output");
+        Self {
+            component,
+            inputs,
+            output,
+            messages: Vec::new(),
+        }
+    }
+}
+
+impl_has_component!(Merge);
+
+impl<T: Message> Atomic for Merge<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_values(&self.output, &self.messages);
+    }
+
+    fn delta_int(&mut self) {
+        self.messages.clear();
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        for port in self.inputs.iter() {
+            self.messages.extend_from_slice(input.get_values(port));
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.messages.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+/// Atomic model that forwards a copy of every message it receives through its `input` port
+/// to all its output ports (`output_0`, `output_1`, etc.). Messages are forwarded immediately.
+pub struct Split<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    outputs: Vec<OutPort<T>>,
+    /// Messages to be forwarded.
+    messages: Vec<T>,
+}
+
+impl<T: Message> Split<T> {
+    /// Creates a new split with `n_outputs` output ports.
+    pub fn new(name: &str, n_outputs: usize) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let outputs = (0..n_outputs)
+            .map(|i| component.add_traced_out_port(&format!("output_{i}")))
+            .collect();
+        Self {
+            component,
+            input,
+            outputs,
+            messages: Vec::new(),
+        }
+    }
+}
+
+impl_has_component!(Split);
+
+impl<T: Message> Atomic for Split<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        for port in self.outputs.iter() {
+            output.add_values(port, &self.messages);
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.messages.clear();
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        self.messages
+            .extend_from_slice(input.get_values(&self.input));
+    }
+
+    fn ta(&self) -> f64 {
+        match self.messages.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_merge_split() {
+        // The input is split in two, and the copies are tagged and merged again
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<i32>("input");
+        coupled.add_out_port::<i32>("output");
+        coupled.add_component(Box::new(Split::<i32>::new("split", 2)));
+        coupled.add_component(Box::new(Merge::<i32>::new("merge", 2)));
+        coupled.add_eic("input", "split", "input");
+        // Messages are merged in the order of the input ports, not of the couplings
+        coupled.add_ic_map("split", "output_1", "merge", "input_1", |x: &i32| Some(-x));
+        coupled.add_ic("split", "output_0", "merge", "input_0");
+        coupled.add_eoc("merge", "output", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<i32>("output");
+        simulator.start();
+        simulator.inject("input", &[1, 2], 1.);
+        simulator.inject("input", &[3], 2.);
+        simulator.simulate_until(3.);
+        let expected = [(1., 1), (1., 2), (1., -1), (1., -2), (2., 3), (2., -3)];
+        assert_eq!(&expected, simulator.get_output("output").unwrap());
+    }
+}
@@ -0,0 +1,181 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Order of the messages of a priority queue.
+type Order<T> = Box<dyn Fn(&T, &T) -> Ordering + Send + Sync>;
+
+/// Atomic model that stores messages until the next model is ready to process them.
+///
+/// Messages are received through the `input` port. The queue sends one message through
+/// the `output` port and waits for a message in the `ready` port before sending the next one.
+/// Initially, the next model is assumed to be ready. Messages are sent in arrival order
+/// (FIFO) or according to their priority (see [`Queue::priority`]).
+pub struct Queue<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    ready: InPort<()>,
+    output: OutPort<T>,
+    /// Messages waiting in the queue.
+    messages: VecDeque<T>,
+    /// Whether the next model is ready to process a message or not.
+    is_ready: bool,
+    /// Order of the messages (if any). Messages that are less than others are sent first.
+    order: Option<Order<T>>,
+}
+
+impl<T: Message> Queue<T> {
+    /// Creates a new FIFO queue.
+    pub fn new(name: &str) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let ready = component.add_traced_in_port("ready");
+        let output = component.add_traced_out_port("output");
+        Self {
+            component,
+            input,
+            ready,
+            output,
+            messages: VecDeque::new(),
+            is_ready: true,
+            order: None,
+        }
+    }
+
+    /// Creates a new priority queue. Messages that are less than others according to `order`
+    /// are sent first. Messages with the same priority are sent in arrival order.
+    pub fn priority(
+        name: &str,
+        order: impl Fn(&T, &T) -> Ordering + Send + Sync + 'static,
+    ) -> Self {
+        let mut queue = Self::new(name);
+        queue.order = Some(Box::new(order));
+        queue
+    }
+
+    /// Returns the number of messages in the queue.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Adds a message to the queue according to its order.
+    fn push(&mut self, message: T) {
+        match &self.order {
+            Some(order) => {
+                let i = self
+                    .messages
+                    .partition_point(|m| order(m, &message) != Ordering::Greater);
+                self.messages.insert(i, message);
+            }
+            None => self.messages.push_back(message),
+        }
+    }
+}
+
+impl_has_component!(Queue);
+
+impl<T: Message> Atomic for Queue<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        if let Some(message) = self.messages.front() {
+            output.add_value(&self.output, message.clone());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.messages.pop_front();
+        self.is_ready = false;
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        if !input.is_empty(&self.ready) {
+            self.is_ready = true;
+        }
+        for message in input.get_values(&self.input).to_vec() {
+            self.push(message);
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.is_ready && !self.messages.is_empty() {
+            true => 0.,
+            false => f64::INFINITY,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    /// Returns a simulator of a coupled model with the queue connected to its ports.
+    fn create_simulator(queue: Queue<char>) -> RootCoordinator<Coupled> {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<char>("input");
+        coupled.add_in_port::<()>("ready");
+        coupled.add_out_port::<char>("output");
+        coupled.add_component(Box::new(queue));
+        coupled.add_eic("input", "queue", "input");
+        coupled.add_eic("ready", "queue", "ready");
+        coupled.add_eoc("queue", "output", "output");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<char>("output");
+        simulator.start();
+        simulator
+    }
+
+    #[test]
+    fn test_fifo() {
+        let mut simulator = create_simulator(Queue::new("queue"));
+        // The next model is initially ready
+        simulator.inject("input", &['a', 'b'], 1.);
+        simulator.inject("input", &['c'], 2.);
+        simulator.inject("ready", &[()], 3.);
+        simulator.inject("ready", &[()], 4.);
+        // Ready notifications without messages do not send anything
+        simulator.inject("ready", &[()], 5.);
+        simulator.inject("input", &['d'], 6.);
+        simulator.simulate_until(10.);
+        let expected = [(1., 'a'), (3., 'b'), (4., 'c'), (6., 'd')];
+        assert_eq!(&expected, simulator.get_output("output").unwrap());
+    }
+
+    #[test]
+    fn test_priority_ties() {
+        // Uppercase letters go first
+        let queue = Queue::priority("queue", |a: &char, b: &char| {
+            a.is_lowercase().cmp(&b.is_lowercase())
+        });
+        let mut simulator = create_simulator(queue);
+        // The first message is sent immediately, and the rest wait in the queue
+        simulator.inject("input", &['a', 'B', 'c', 'D'], 1.);
+        simulator.inject("input", &['E', 'f'], 1.5);
+        for t in 2..7 {
+            simulator.inject("ready", &[()], t as f64);
+        }
+        simulator.simulate_until(10.);
+        // Messages with the same priority keep their arrival order
+        let expected = [
+            (1., 'B'),
+            (2., 'D'),
+            (3., 'E'),
+            (4., 'a'),
+            (5., 'c'),
+            (6., 'f'),
+        ];
+        assert_eq!(&expected, simulator.get_output("output").unwrap());
+    }
+}
@@ -0,0 +1,249 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+
+/// Routing function of a router.
+type Route<T> = Box<dyn Fn(&T) -> Option<usize> + Send + Sync>;
+
+/// Atomic model that forwards every message it receives through its `input` port to one of
+/// its output ports (`output_0`, `output_1`, etc.), according to a routing function.
+/// Messages are forwarded immediately.
+pub struct Router<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    outputs: Vec<OutPort<T>>,
+    /// Routing function. It returns the index of the output port of a message (if any).
+    route: Route<T>,
+    /// Messages to be forwarded, together with the index of their output port.
+    messages: Vec<(usize, T)>,
+}
+
+impl<T: Message> Router<T> {
+    /// Creates a new router with `n_outputs` output ports. `route` returns the index of the
+    /// output port of a message, or [`None`] if the message must be discarded.
+    /// Routing a message to an output port that does not exist causes a panic.
+    pub fn new(
+        name: &str,
+        n_outputs: usize,
+        route: impl Fn(&T) -> Option<usize> + Send + Sync + 'static,
+    ) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let outputs = (0..n_outputs)
+            .map(|i| component.add_traced_out_port(&format!("output_{i}")))
+            .collect();
+        Self {
+            component,
+            input,
+            outputs,
+            route: Box::new(route),
+            messages: Vec::new(),
+        }
+    }
+}
+
+impl_has_component!(Router);
+
+impl<T: Message> Atomic for Router<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        for (i, message) in self.messages.iter() {
+            output.add_value(&self.outputs[*i], message.clone());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.messages.clear();
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        for message in input.get_values(&self.input) {
+            if let Some(i) = (self.route)(message) {
+                if i >= self.outputs.len() {
+                    panic!(
+                        "router {} has no output port {i}",
+                        self.component.get_name()
+                    );
+                }
+                self.messages.push((i, message.clone()));
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        match self.messages.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+/// Atomic model that forwards every message it receives through its `input` port to its
+/// selected output port (`output_0`, `output_1`, etc.). Initially, the first output port is
+/// selected. The selected port changes when the switch receives its index through the `select`
+/// port. Messages are forwarded immediately. If the switch receives a new selection and messages
+/// at the same time, the messages are forwarded to the new selected port.
+pub struct Switch<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    select: InPort<usize>,
+    outputs: Vec<OutPort<T>>,
+    /// Index of the selected output port.
+    selected: usize,
+    /// Messages to be forwarded.
+    messages: Vec<T>,
+}
+
+impl<T: Message> Switch<T> {
+    /// Creates a new switch with `n_outputs` output ports.
+    /// Selecting an output port that does not exist causes a panic.
+    pub fn new(name: &str, n_outputs: usize) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let select = component.add_traced_in_port("select");
+        let outputs = (0..n_outputs)
+            .map(|i| component.add_traced_out_port(&format!("output_{i}")))
+            .collect();
+        Self {
+            component,
+            input,
+            select,
+            outputs,
+            selected: 0,
+            messages: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the selected output port.
+    #[inline]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+}
+
+impl_has_component!(Switch);
+
+impl<T: Message> Atomic for Switch<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_values(&self.outputs[self.selected], &self.messages);
+    }
+
+    fn delta_int(&mut self) {
+        self.messages.clear();
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        if let Some(&selected) = input.get_values(&self.select).last() {
+            if selected >= self.outputs.len() {
+                panic!(
+                    "switch {} has no output port {selected}",
+                    self.component.get_name()
+                );
+            }
+            self.selected = selected;
+        }
+        self.messages
+            .extend_from_slice(input.get_values(&self.input));
+    }
+
+    fn ta(&self) -> f64 {
+        match self.messages.is_empty() {
+            true => f64::INFINITY,
+            false => 0.,
+        }
+    }
+
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    /// Returns a simulator of a coupled model with the router (or switch) connected to its ports.
+    fn create_simulator(
+        model: impl Atomic<Time = f64> + crate::DynRef,
+        select: bool,
+    ) -> RootCoordinator<Coupled> {
+        let mut coupled = Coupled::new("root");
+        coupled.add_component(Box::new(model));
+        coupled.add_in_port::<i32>("input");
+        coupled.add_eic("input", "model", "input");
+        if select {
+            coupled.add_in_port::<usize>("select");
+            coupled.add_eic("select", "model", "select");
+        }
+        for port in ["output_0", "output_1"] {
+            coupled.add_out_port::<i32>(port);
+            coupled.add_eoc("model", port, port);
+        }
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<i32>("output_0");
+        simulator.collect_output::<i32>("output_1");
+        simulator.start();
+        simulator
+    }
+
+    #[test]
+    fn test_router() {
+        // Multiples of 3 are dropped
+        let router = Router::new("model", 2, |x: &i32| {
+            (x % 3 != 0).then_some((x % 3 - 1) as usize)
+        });
+        let mut simulator = create_simulator(router, false);
+        simulator.inject("input", &[1, 2, 3, 4], 1.);
+        simulator.inject("input", &[6], 2.);
+        simulator.inject("input", &[5], 3.);
+        simulator.simulate_until(10.);
+        assert_eq!(
+            &[(1., 1), (1., 4)],
+            simulator.get_output("output_0").unwrap()
+        );
+        assert_eq!(
+            &[(1., 2), (3., 5)],
+            simulator.get_output("output_1").unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "router model has no output port 2")]
+    fn test_router_out_of_range() {
+        let router = Router::new("model", 2, |x: &i32| Some(*x as usize));
+        let mut simulator = create_simulator(router, false);
+        simulator.inject("input", &[1, 2], 1.);
+    }
+
+    #[test]
+    fn test_switch() {
+        let mut simulator = create_simulator(Switch::<i32>::new("model", 2), true);
+        simulator.inject("input", &[1], 1.);
+        simulator.inject::<usize>("select", &[1], 2.);
+        simulator.inject("input", &[2, 3], 3.);
+        // Only the last selection counts
+        simulator.inject::<usize>("select", &[1, 0], 4.);
+        simulator.inject("input", &[4], 5.);
+        simulator.simulate_until(10.);
+        assert_eq!(
+            &[(1., 1), (5., 4)],
+            simulator.get_output("output_0").unwrap()
+        );
+        assert_eq!(
+            &[(3., 2), (3., 3)],
+            simulator.get_output("output_1").unwrap()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "switch model has no output port 2")]
+    fn test_switch_out_of_range() {
+        let mut simulator = create_simulator(Switch::<i32>::new("model", 2), true);
+        simulator.inject::<usize>("select", &[2], 1.);
+    }
+}
@@ -0,0 +1,113 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+
+/// Atomic model that processes one message at a time.
+///
+/// Messages are received through the `input` port. When the server is idle, it starts processing
+/// the first received message. After the service time, it sends the message through the `output`
+/// port and a notification through the `ready` port. Messages received while the server is busy
+/// are discarded, so servers are usually preceded by a [`Queue`](super::Queue).
+pub struct Server<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    output: OutPort<T>,
+    ready: OutPort<()>,
+    /// Time remaining until the current message is processed.
+    sigma: f64,
+    /// Message being processed (if any).
+    message: Option<T>,
+    /// Function that returns the service time of a message.
+    service_time: Box<dyn FnMut(&T) -> f64 + Send + Sync>,
+}
+
+impl<T: Message> Server<T> {
+    /// Creates a new server. `service_time` returns the time required for processing a message.
+    pub fn new(name: &str, service_time: impl FnMut(&T) -> f64 + Send + Sync + 'static) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        let output = component.add_traced_out_port("output");
+        let ready = component.add_traced_out_port("ready");
+        Self {
+            component,
+            input,
+            output,
+            ready,
+            sigma: f64::INFINITY,
+            message: None,
+            service_time: Box::new(service_time),
+        }
+    }
+
+    /// Returns `true` if the server is processing a message.
+    #[inline]
+    pub fn is_busy(&self) -> bool {
+        self.message.is_some()
+    }
+}
+
+impl_has_component!(Server);
+
+impl<T: Message> Atomic for Server<T> {
+    fn lambda(&self, output: &OutputCtx) {
+        if let Some(message) = &self.message {
+            output.add_value(&self.output, message.clone());
+            output.add_value(&self.ready, ());
+        }
+    }
+
+    fn delta_int(&mut self) {
+        self.sigma = f64::INFINITY;
+        self.message = None;
+    }
+
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+        self.sigma -= e;
+        if self.message.is_none() {
+            if let Some(message) = input.get_values(&self.input).first() {
+                self.sigma = (self.service_time)(message);
+                self.message = Some(message.clone());
+            }
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_server_busy() {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<char>("input");
+        coupled.add_out_port::<char>("output");
+        coupled.add_out_port::<()>("ready");
+        coupled.add_component(Box::new(Server::new("server", |_: &char| 2.)));
+        coupled.add_eic("input", "server", "input");
+        coupled.add_eoc("server", "output", "output");
+        coupled.add_eoc("server", "ready", "ready");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.collect_output::<char>("output");
+        simulator.collect_output::<()>("ready");
+        simulator.start();
+        // Only the first message is processed, and the others are discarded
+        simulator.inject("input", &['a', 'b'], 1.);
+        simulator.inject("input", &['c'], 2.);
+        // The server is idle again when it sends the processed message
+        simulator.inject("input", &['d'], 3.);
+        simulator.inject("input", &['e'], 4.5);
+        simulator.simulate_until(10.);
+        assert_eq!(
+            &[(3., 'a'), (5., 'd')],
+            simulator.get_output("output").unwrap()
+        );
+        assert_eq!(
+            &[(3., ()), (5., ())],
+            simulator.get_output("ready").unwrap()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use super::{impl_has_component, Message};
+use crate::modeling::*;
+use std::sync::{Arc, Mutex};
+
+/// Messages collected by a [`Sink`], together with the time at which they were received.
+pub type Collected<T> = Arc<Mutex<Vec<(f64, T)>>>;
+
+/// Atomic model that collects all the messages it receives through its `input` port.
+/// Collected messages can be read during or after the simulation with [`Sink::collected`].
+pub struct Sink<T: Message> {
+    component: Component,
+    input: InPort<T>,
+    /// Collected messages.
+    collected: Collected<T>,
+}
+
+impl<T: Message> Sink<T> {
+    /// Creates a new sink.
+    pub fn new(name: &str) -> Self {
+        let mut component = Component::new(name);
+        let input = component.add_traced_in_port("input");
+        Self {
+            component,
+            input,
+            collected: Arc::default(),
+        }
+    }
+
+    /// Returns a handle to the messages collected by the sink.
+    /// It must be obtained before moving the sink to a coupled model.
+    pub fn collected(&self) -> Collected<T> {
+        self.collected.clone()
+    }
+}
+
+impl_has_component!(Sink);
+
+impl<T: Message> Atomic for Sink<T> {
+    fn lambda(&self, _output: &OutputCtx) {}
+
+    fn delta_int(&mut self) {}
+
+    fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+        let t = self.component.get_t_last() + e;
+        let mut collected = self.collected.lock().unwrap();
+        for message in input.get_values(&self.input) {
+            collected.push((t, message.clone()));
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        f64::INFINITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::RootCoordinator;
+
+    #[test]
+    fn test_sink() {
+        let sink = Sink::<char>::new("sink");
+        let collected = sink.collected();
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<char>("input");
+        coupled.add_component(Box::new(sink));
+        coupled.add_eic("input", "sink", "input");
+        let mut simulator = RootCoordinator::new(coupled);
+        simulator.start();
+        simulator.inject("input", &['a', 'b'], 1.5);
+        // Collected messages can be read during the simulation
+        assert_eq!(vec![(1.5, 'a'), (1.5, 'b')], *collected.lock().unwrap());
+        simulator.inject("input", &['c'], 4.);
+        simulator.finish();
+        let expected = vec![(1.5, 'a'), (1.5, 'b'), (4., 'c')];
+        assert_eq!(expected, *collected.lock().unwrap());
+    }
+}
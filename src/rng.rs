@@ -0,0 +1,179 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+pub use rand::{Rng, RngCore};
+
+/// Random number generator of the streams of the models.
+pub type ModelRng = ChaCha8Rng;
+
+/// Random number stream of a component. It is derived from a master seed and the full path of the
+/// component, so adding or removing other components does not change the stream.
+#[derive(Debug, Clone)]
+pub(crate) struct RngStream {
+    /// Master seed of the simulation.
+    pub(crate) seed: u64,
+    /// Full path of the component.
+    pub(crate) path: String,
+    /// Random number generator of the component.
+    pub(crate) rng: ModelRng,
+}
+
+impl RngStream {
+    /// Creates the random number stream of the component with the provided full path.
+    /// The master seed sets the key of the generator, and the hash of the path selects its stream.
+    pub(crate) fn new(seed: u64, path: &str) -> Self {
+        let mut rng = ModelRng::seed_from_u64(seed);
+        rng.set_stream(path_hash(path));
+        Self {
+            seed,
+            path: path.to_string(),
+            rng,
+        }
+    }
+}
+
+/// Saved state of a random number stream. It is used by simulation checkpoints.
+#[cfg(feature = "serde")]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub(crate) struct RngState {
+    /// Master seed of the simulation.
+    seed: u64,
+    /// Full path of the component.
+    path: String,
+    /// Stream of the generator.
+    stream: u64,
+    /// Position of the generator in its stream, in 32-bit words.
+    word_pos: u128,
+}
+
+#[cfg(feature = "serde")]
+impl RngStream {
+    /// Returns the current state of the random number stream.
+    pub(crate) fn save(&self) -> RngState {
+        RngState {
+            seed: self.seed,
+            path: self.path.clone(),
+            stream: self.rng.get_stream(),
+            word_pos: self.rng.get_word_pos(),
+        }
+    }
+
+    /// Creates a random number stream from a previously saved state.
+    pub(crate) fn restore(state: RngState) -> Self {
+        let mut rng = ModelRng::seed_from_u64(state.seed);
+        rng.set_stream(state.stream);
+        rng.set_word_pos(state.word_pos);
+        Self {
+            seed: state.seed,
+            path: state.path,
+            rng,
+        }
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of a path. Unlike the hashers of the standard library,
+/// it is guaranteed to be the same in every platform and Rust version.
+fn path_hash(path: &str) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    path.bytes()
+        .fold(OFFSET, |hash, b| (hash ^ b as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{Atomic, Component, Coupled, HasComponent, InputCtx, OutPort, OutputCtx};
+    use crate::simulation::RootCoordinator;
+
+    /// Atomic model that sends a random number every second.
+    struct Noise {
+        component: Component,
+        value: u64,
+        output: OutPort<u64>,
+    }
+
+    impl Noise {
+        fn new(name: &str) -> Self {
+            let mut component = Component::new(name);
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                value: 0,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Noise {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Noise {
+        fn start(&mut self) {
+            self.value = self.component.rng().gen();
+        }
+
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.value);
+        }
+
+        fn delta_int(&mut self) {
+            self.value = self.component.rng().gen();
+        }
+
+        fn delta_ext(&mut self, _e: f64, _input: &InputCtx) {}
+
+        fn ta(&self) -> f64 {
+            1.
+        }
+    }
+
+    #[test]
+    fn test_rng_streams() {
+        let simulate = |seed: u64, names: &[&str]| {
+            let mut coupled = Coupled::new("root");
+            for &name in names {
+                coupled.add_out_port::<u64>(name);
+                coupled.add_component(Box::new(Noise::new(name)));
+                coupled.add_eoc(name, "output", name);
+            }
+            let mut simulator = RootCoordinator::new(coupled);
+            simulator.set_seed(seed);
+            for &name in names {
+                simulator.collect_output::<u64>(name);
+            }
+            simulator.simulate(3.5);
+            let output: Vec<Vec<(f64, u64)>> = names
+                .iter()
+                .map(|&name| simulator.get_output::<u64>(name).unwrap().to_vec())
+                .collect();
+            output
+        };
+
+        let output = simulate(1, &["a", "b"]);
+        assert_eq!(3, output[0].len());
+        assert_ne!(output[0], output[1]);
+        assert_eq!(output, simulate(1, &["a", "b"]));
+        // Adding another model does not change the random numbers of the others
+        assert_eq!(output[..], simulate(1, &["c", "a", "b"])[1..]);
+        assert_ne!(output, simulate(2, &["a", "b"]));
+    }
+
+    #[test]
+    fn test_path_hash() {
+        // Reference values of the 64-bit FNV-1a hash
+        assert_eq!(0xcbf29ce484222325, path_hash(""));
+        assert_eq!(0xaf63dc4c8601ec8c, path_hash("a"));
+        assert_eq!(0x85944171f73967e8, path_hash("foobar"));
+        assert_ne!(path_hash("root.a"), path_hash("root.b"));
+    }
+}
@@ -1,16 +1,34 @@
-use crate::modeling::{Atomic, Component, Coupled};
-use crate::DynRef;
+#[cfg(feature = "serde")]
+mod checkpoint;
+mod observer;
+mod output;
+mod scheduler;
+mod trace;
+
+use crate::modeling::port::Port;
+#[cfg(feature = "serde")]
+use crate::modeling::DynSnapshot;
+use crate::modeling::{Atomic, Component, Coupled, HasComponent, InputCtx, Issue, OutputCtx, Time};
+use crate::{DynRef, Error, Result};
+pub use observer::{CouplingInfo, SimObserver};
+use output::{OutputCallback, OutputHistory, OutputListener};
 #[cfg(feature = "par_any")]
 use rayon::prelude::*;
+pub(crate) use scheduler::Scheduler;
+use std::collections::HashMap;
+use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+pub use trace::TraceWriter;
 
 /// Interface for simulating DEVS models. All DEVS models must implement this trait.
-pub trait Simulator: DynRef {
+/// `T` is the type used for representing simulation times. By default, it is [`f64`].
+pub trait Simulator<T: Time = f64>: DynRef {
     /// Returns reference to inner [`Component`].
-    fn get_component(&self) -> &Component;
+    fn get_component(&self) -> &Component<T>;
 
     /// Returns mutable reference to inner [`Component`].
-    fn get_component_mut(&mut self) -> &mut Component;
+    fn get_component_mut(&mut self) -> &mut Component<T>;
 
     /// Returns the name of the inner DEVS [`Component`].
     #[inline]
@@ -20,19 +38,19 @@ pub trait Simulator: DynRef {
 
     /// Returns the time for the last state transition of the inner DEVS [`Component`].
     #[inline]
-    fn get_t_last(&self) -> f64 {
+    fn get_t_last(&self) -> T {
         self.get_component().get_t_last()
     }
 
     /// Returns the time for the next state transition of the inner DEVS [`Component`].
     #[inline]
-    fn get_t_next(&self) -> f64 {
+    fn get_t_next(&self) -> T {
         self.get_component().get_t_next()
     }
 
     /// Sets the tine for the last and next state transitions of the inner DEVS [`Component`].
     #[inline]
-    fn set_sim_t(&mut self, t_last: f64, t_next: f64) {
+    fn set_sim_t(&mut self, t_last: T, t_next: T) {
         self.get_component_mut().set_sim_t(t_last, t_next);
     }
 
@@ -59,105 +77,241 @@ pub trait Simulator: DynRef {
         }
     }
 
+    /// Returns the name of the type of the model (e.g., `xdevs::modeling::Coupled<f64>`).
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Returns a mutable reference to the model as a [`Coupled`] model.
+    /// Atomic models return [`None`].
+    #[inline]
+    fn as_coupled_mut(&mut self) -> Option<&mut Coupled<T>> {
+        None
+    }
+
+    /// Returns a reference to the model as a [`Coupled`] model.
+    /// Atomic models return [`None`].
+    #[inline]
+    fn as_coupled(&self) -> Option<&Coupled<T>> {
+        None
+    }
+
+    /// Returns a reference to the model as a [`DynSnapshot`] trait object (if it supports snapshots).
+    #[cfg(feature = "serde")]
+    #[inline]
+    fn as_snapshot(&self) -> Option<&dyn DynSnapshot> {
+        None
+    }
+
+    /// Returns a mutable reference to the model as a [`DynSnapshot`] trait object (if it supports snapshots).
+    #[cfg(feature = "serde")]
+    #[inline]
+    fn as_snapshot_mut(&mut self) -> Option<&mut dyn DynSnapshot> {
+        None
+    }
+
+    /// Returns `true` if the model may send messages in zero time after receiving a message.
+    /// It is used by [`Coupled::validate`] to detect algebraic loops.
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        false
+    }
+
     /// It starts the simulation, setting the initial time to t_start.
-    fn start(&mut self, t_start: f64) -> f64;
+    fn start(&mut self, t_start: T) -> T;
 
     /// It stops the simulation, setting the last time to t_stop.
-    fn stop(&mut self, t_stop: f64);
+    fn stop(&mut self, t_stop: T);
 
     /// Executes output functions and propagates messages according to ICs and EOCs.
-    fn collection(&mut self, t: f64);
+    fn collection(&mut self, t: T);
 
     /// Propagates messages according to EICs and executes model transition functions.
-    fn transition(&mut self, t: f64) -> f64;
+    fn transition(&mut self, t: T) -> T;
+
+    /// Propagates messages according to EICs and only executes external transition functions,
+    /// even if the model is imminent. It is used by coupled models in Classic DEVS mode
+    /// for imminent components that receive messages but were not selected (see [`Coupled::set_select`]).
+    fn transition_ext(&mut self, t: T) -> T;
+}
+
+/// Notifies a simulation observer about all the messages received by a model.
+#[inline]
+fn notify_input<T: Time>(
+    model: &dyn Simulator<T>,
+    path: &str,
+    observer: &dyn SimObserver<T>,
+    t: T,
+) {
+    let mut f = |port: &str, value: &dyn Debug| observer.on_input(path, t, port, value);
+    // Safety: simulator reading its input after executing its transition function
+    unsafe { model.get_component().for_each_input(&mut f) };
 }
 
-impl<T: Atomic + DynRef> Simulator for T {
+impl<T: Time, M: Atomic<Time = T> + DynRef> Simulator<T> for M {
+    #[inline]
+    fn get_component(&self) -> &Component<T> {
+        HasComponent::get_component(self)
+    }
+
+    #[inline]
+    fn get_component_mut(&mut self) -> &mut Component<T> {
+        HasComponent::get_component_mut(self)
+    }
+
+    #[cfg(feature = "serde")]
     #[inline]
-    fn get_component(&self) -> &Component {
-        Atomic::get_component(self)
+    fn as_snapshot(&self) -> Option<&dyn DynSnapshot> {
+        Atomic::as_snapshot(self)
     }
 
+    #[cfg(feature = "serde")]
     #[inline]
-    fn get_component_mut(&mut self) -> &mut Component {
-        Atomic::get_component_mut(self)
+    fn as_snapshot_mut(&mut self) -> Option<&mut dyn DynSnapshot> {
+        Atomic::as_snapshot_mut(self)
     }
 
-    fn start(&mut self, t_start: f64) -> f64 {
+    #[inline]
+    fn may_emit_in_zero_time(&self) -> bool {
+        Atomic::may_emit_in_zero_time(self)
+    }
+
+    fn start(&mut self, t_start: T) -> T {
         Atomic::start(self);
-        let t_next = t_start + self.ta();
+        let t_next = t_start.advance(self.ta());
         self.set_sim_t(t_start, t_next);
+        if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+            observer.on_start(path, t_start);
+        }
         t_next
     }
 
-    fn stop(&mut self, t_stop: f64) {
-        self.set_sim_t(t_stop, f64::INFINITY);
+    fn stop(&mut self, t_stop: T) {
+        self.set_sim_t(t_stop, T::INFINITY);
         Atomic::stop(self);
+        if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+            observer.on_stop(path, t_stop);
+        }
     }
 
-    fn collection(&mut self, t: f64) {
+    fn collection(&mut self, t: T) {
         if t >= self.get_t_next() {
-            Atomic::lambda(self)
+            Atomic::lambda(self, &OutputCtx::new(Simulator::get_component(self)));
+            let component = Simulator::get_component(self);
+            if let Some((path, observer)) = component.get_observer() {
+                observer.on_lambda(path, t);
+                let mut f =
+                    |port: &str, value: &dyn Debug| observer.on_output(path, t, port, value);
+                // Safety: simulator reading its output after executing the output function
+                unsafe { component.for_each_output(&mut f) };
+            }
         }
     }
 
-    fn transition(&mut self, t: f64) -> f64 {
+    fn transition(&mut self, t: T) -> T {
         let t_next = self.get_t_next();
         // Safety: simulator executing its transition function
         if !unsafe { self.get_component().is_input_empty() } {
             if t == t_next {
-                Atomic::delta_conf(self);
+                let input = InputCtx::new(Simulator::get_component(self));
+                Atomic::delta_conf(self, &input);
+                if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+                    notify_input(self, path, observer, t);
+                    observer.on_delta_conf(path, t);
+                }
                 self.clear_output();
             } else {
-                let e = t - self.get_t_last();
-                Atomic::delta_ext(self, e);
+                let e = t.elapsed_since(self.get_t_last());
+                let input = InputCtx::new(Simulator::get_component(self));
+                Atomic::delta_ext(self, e, &input);
+                if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+                    notify_input(self, path, observer, t);
+                    observer.on_delta_ext(path, t, e);
+                }
             }
             self.clear_input();
         } else if t == t_next {
             Atomic::delta_int(self);
+            if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+                observer.on_delta_int(path, t);
+            }
             self.clear_output();
         } else {
             return t_next;
         }
-        let t_next = t + Atomic::ta(self);
+        let t_next = t.advance(Atomic::ta(self));
+        self.set_sim_t(t, t_next);
+        t_next
+    }
+
+    fn transition_ext(&mut self, t: T) -> T {
+        // Safety: simulator checking if its input is empty
+        if unsafe { self.get_component().is_input_empty() } {
+            return self.get_t_next();
+        }
+        let e = t.elapsed_since(self.get_t_last());
+        let input = InputCtx::new(Simulator::get_component(self));
+        Atomic::delta_ext(self, e, &input);
+        if let Some((path, observer)) = Simulator::get_component(self).get_observer() {
+            notify_input(self, path, observer, t);
+            observer.on_delta_ext(path, t, e);
+        }
+        self.clear_input();
+        let t_next = t.advance(Atomic::ta(self));
         self.set_sim_t(t, t_next);
         t_next
     }
 }
 
-impl Simulator for Coupled {
+impl<T: Time> Simulator<T> for Coupled<T> {
     #[inline]
-    fn get_component(&self) -> &Component {
+    fn get_component(&self) -> &Component<T> {
         &self.component
     }
 
     #[inline]
-    fn get_component_mut(&mut self) -> &mut Component {
+    fn get_component_mut(&mut self) -> &mut Component<T> {
         &mut self.component
     }
 
+    #[inline]
+    fn as_coupled_mut(&mut self) -> Option<&mut Coupled<T>> {
+        Some(self)
+    }
+
+    #[inline]
+    fn as_coupled(&self) -> Option<&Coupled<T>> {
+        Some(self)
+    }
+
     /// Iterates over all the subcomponents to call their [`Simulator::start`]
-    /// method and obtain the next simulation time.
+    /// method and schedule them according to their next simulation time.
     ///
     /// If the feature `par_start` is activated, the iteration is parallelized.
-    fn start(&mut self, t_start: f64) -> f64 {
+    fn start(&mut self, t_start: T) -> T {
         #[cfg(feature = "par_start")]
         let iter = self.components.par_iter_mut();
         #[cfg(not(feature = "par_start"))]
         let iter = self.components.iter_mut();
-        // we obtain the minimum next time of all the subcomponents
-        let t_next = iter
-            .map(|c| c.start(t_start))
-            .min_by(|a, b| a.total_cmp(b))
-            .unwrap_or(f64::INFINITY);
-        // and set the inner component's last and next times
+        // we obtain the next time of all the subcomponents
+        let t_nexts: Vec<T> = iter.map(|c| c.start(t_start)).collect();
+        // and schedule them accordingly
+        self.scheduler.reset(t_nexts.len());
+        for (i, t_next) in t_nexts.into_iter().enumerate() {
+            self.scheduler.schedule(i, t_next);
+        }
+        // finally, we set the inner component's last and next times
+        let t_next = self.next_time();
         self.set_sim_t(t_start, t_next);
+        if let Some((path, observer)) = self.component.get_observer() {
+            observer.on_start(path, t_start);
+        }
 
         #[cfg(feature = "par_couplings")]
         {
             self.build_par_eics();
-            self.build_par_eocs();
-            self.build_par_ics();
+            self.build_par_xxcs();
         }
 
         t_next
@@ -167,128 +321,825 @@ impl Simulator for Coupled {
     /// method and obtain the next simulation time.
     ///
     /// If the feature `par_stop` is activated, the iteration is parallelized.
-    fn stop(&mut self, t_stop: f64) {
+    fn stop(&mut self, t_stop: T) {
         #[cfg(feature = "par_stop")]
         let iter = self.components.par_iter_mut();
         #[cfg(not(feature = "par_stop"))]
         let iter = self.components.iter_mut();
         iter.for_each(|c| c.stop(t_stop));
+        self.scheduler.reset(self.components.len());
         // we set the inner component's last and next times accordingly
-        self.set_sim_t(t_stop, f64::INFINITY);
+        self.set_sim_t(t_stop, T::INFINITY);
+        if let Some((path, observer)) = self.component.get_observer() {
+            observer.on_stop(path, t_stop);
+        }
     }
 
-    /// Iterates over all the subcomponents to call their [`Simulator::collection`] method.
+    /// Marks as active all the imminent subcomponents and calls their [`Simulator::collection`] method.
+    /// In Classic DEVS mode, only the imminent subcomponent chosen by the tie-breaking function
+    /// is marked as active (see [`Coupled::set_select`]).
     /// If the feature `par_collection` is activated, the iteration is parallelized.
-    /// Then, it iterates over all the EOCs and propagates messages accordingly.
+    /// Then, it propagates messages according to the EOCs and ICs that leave from imminent subcomponents.
+    /// Subcomponents that receive messages are marked as active.
     ///
-    /// If the feature `par_eoc` is activated, the iteration is parallelized.
-    fn collection(&mut self, t: f64) {
+    /// If the feature `par_couplings` is activated, the propagation is parallelized.
+    fn collection(&mut self, t: T) {
         if t >= self.get_t_next() {
+            if self.is_classic() {
+                self.activate_selected(t);
+            } else {
+                self.activate_imminent(t);
+            }
+            let observer = self.component.get_observer();
+
             #[cfg(feature = "par_collection")]
-            let iter = self.components.par_iter_mut();
+            self.components
+                .par_iter_mut()
+                .zip(self.scheduler.active_flags().par_iter())
+                .filter(|(_, &active)| active)
+                .for_each(|(c, _)| c.collection(t));
             #[cfg(not(feature = "par_collection"))]
-            let iter = self.components.iter_mut();
-            iter.for_each(|c| c.collection(t));
+            for &i in self.scheduler.active() {
+                self.components[i].collection(t);
+            }
 
             #[cfg(feature = "par_couplings")]
-            self.par_xxcs.par_iter().for_each(|coups| {
-                coups.iter().for_each(|(port_to, port_from)| {
-                    // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-            });
+            {
+                let receivers: Vec<usize> = self
+                    .par_xxcs
+                    .par_iter()
+                    .filter_map(|(dest, coups)| {
+                        let mut received = false;
+                        for coupling in coups.iter() {
+                            // Safety: coupled model propagating messages
+                            if unsafe { coupling.propagate() } {
+                                if let Some((path, observer)) = observer {
+                                    observer.on_propagate(path, t, &coupling.info());
+                                }
+                                received = true;
+                            }
+                        }
+                        dest.filter(|_| received)
+                    })
+                    .collect();
+                for i in receivers {
+                    self.scheduler.activate(i);
+                }
+            }
 
             #[cfg(not(feature = "par_couplings"))]
             {
-                self.eocs.iter().for_each(|(port_to, port_from)| {
+                // We only propagate couplings that leave from imminent subcomponents.
+                // They are sorted to keep the order in which they were added to the model.
+                self.coups_buf.clear();
+                for &i in self.scheduler.active() {
+                    self.coups_buf.extend_from_slice(&self.comp_eocs[i]);
+                }
+                self.coups_buf.sort_unstable();
+                for &j in self.coups_buf.iter() {
+                    let coupling = &self.eocs[j];
                     // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-                self.ics.iter().for_each(|(port_to, port_from)| {
+                    if unsafe { coupling.propagate() } {
+                        if let Some((path, observer)) = observer {
+                            observer.on_propagate(path, t, &coupling.info());
+                        }
+                    }
+                }
+
+                self.coups_buf.clear();
+                for &i in self.scheduler.active() {
+                    self.coups_buf.extend_from_slice(&self.comp_ics[i]);
+                }
+                self.coups_buf.sort_unstable();
+                for &j in self.coups_buf.iter() {
+                    let coupling = &self.ics[j];
                     // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
+                    if unsafe { coupling.propagate() } {
+                        if let Some((path, observer)) = observer {
+                            observer.on_propagate(path, t, &coupling.info());
+                        }
+                        self.scheduler.activate(self.ic_dests[j]);
+                    }
+                }
             }
         }
     }
 
-    /// Iterates over all the EICs and ICs and propagates messages accordingly.
-    /// If the feature `par_xic` is activated, the iteration is parallelized.
-    /// Then, it iterates over all the subcomponents to:
+    /// Iterates over all the EICs and propagates messages accordingly.
+    /// Subcomponents that receive messages are marked as active.
+    /// If the feature `par_couplings` is activated, the iteration is parallelized.
+    /// Then, it iterates over all the active subcomponents to:
     /// 1. Call their [`Simulator::transition`] method
-    /// 2. Clear their ports
-    /// 3. obtain their next simulation time.
+    ///    (or [`Simulator::transition_ext`] for imminent subcomponents that were not selected in Classic DEVS mode).
+    /// 2. Schedule them according to their next simulation time.
     ///
     /// If the feature `par_transition` is activated, the iteration is parallelized.
-    fn transition(&mut self, t: f64) -> f64 {
-        // Safety: simulator checking if its input is empty
-        let is_external = !unsafe { self.get_component().is_input_empty() };
-        // Propagate messages according to EICs only if there are messages in the input ports
-        if is_external {
-            #[cfg(feature = "par_couplings")]
-            self.par_eics.par_iter().for_each(|coups| {
-                coups.iter().for_each(|(port_to, port_from)| {
-                    // Safety: coupled model propagating messages
-                    unsafe { port_from.propagate(&**port_to) };
-                });
-            });
-            #[cfg(not(feature = "par_couplings"))]
-            self.eics.iter().for_each(|(port_to, port_from)| {
-                // Safety: coupled model propagating messages
-                unsafe { port_from.propagate(&**port_to) };
-            });
-            self.clear_input();
-        }
+    fn transition(&mut self, t: T) -> T {
+        let is_external = self.propagate_eics(t);
         let is_internal = t >= self.get_t_next();
         if is_internal {
             self.clear_output();
         }
         // Nested call only if there are messages in the input ports or if the time has come
         if is_external || is_internal {
-            #[cfg(feature = "par_transition")]
-            let iterator = self.components.par_iter_mut();
-            #[cfg(not(feature = "par_transition"))]
-            let iterator = self.components.iter_mut();
-            let t_next = iterator
-                .map(|c| c.transition(t))
-                .min_by(|a, b| a.total_cmp(b))
-                .unwrap_or(f64::INFINITY);
-            self.set_sim_t(t, t_next);
+            self.transition_active(t, false);
+        }
+        self.get_t_next()
+    }
+
+    /// Iterates over all the EICs and propagates messages accordingly.
+    /// Then, it calls the [`Simulator::transition_ext`] method of the subcomponents that received messages.
+    fn transition_ext(&mut self, t: T) -> T {
+        if self.propagate_eics(t) {
+            self.transition_active(t, true);
         }
         self.get_t_next()
     }
 }
 
+impl<T: Time> Coupled<T> {
+    /// Propagates messages according to EICs if there are messages in the input ports.
+    /// Subcomponents that receive messages are marked as active.
+    /// It returns `true` if the input ports contained messages.
+    fn propagate_eics(&mut self, t: T) -> bool {
+        // Safety: simulator checking if its input is empty
+        if unsafe { self.get_component().is_input_empty() } {
+            return false;
+        }
+        let observer = self.component.get_observer();
+        #[cfg(feature = "par_couplings")]
+        {
+            let receivers: Vec<usize> = self
+                .par_eics
+                .par_iter()
+                .filter_map(|(dest, coups)| {
+                    let mut received = false;
+                    for coupling in coups.iter() {
+                        // Safety: coupled model propagating messages
+                        if unsafe { coupling.propagate() } {
+                            if let Some((path, observer)) = observer {
+                                observer.on_propagate(path, t, &coupling.info());
+                            }
+                            received = true;
+                        }
+                    }
+                    received.then_some(*dest)
+                })
+                .collect();
+            for i in receivers {
+                self.scheduler.activate(i);
+            }
+        }
+        #[cfg(not(feature = "par_couplings"))]
+        for (j, coupling) in self.eics.iter().enumerate() {
+            // Safety: coupled model propagating messages
+            if unsafe { coupling.propagate() } {
+                if let Some((path, observer)) = observer {
+                    observer.on_propagate(path, t, &coupling.info());
+                }
+                self.scheduler.activate(self.eic_dests[j]);
+            }
+        }
+        // Safety: coupled model clearing its input
+        unsafe { self.component.clear_input() };
+        true
+    }
+
+    /// Executes the transition of all the active subcomponents and schedules them again.
+    /// If `external_only` is `true`, subcomponents only execute external transitions.
+    /// In Classic DEVS mode, imminent subcomponents that were not selected also
+    /// execute external transitions only.
+    fn transition_active(&mut self, t: T, external_only: bool) {
+        let selected = self.selected.take();
+        let transition = |i: usize, c: &mut Box<dyn Simulator<T>>| match selected {
+            _ if external_only => c.transition_ext(t),
+            Some(s) if s != i => c.transition_ext(t),
+            _ => c.transition(t),
+        };
+        #[cfg(feature = "par_transition")]
+        {
+            let t_nexts: Vec<(usize, T, Vec<Error>)> = self
+                .components
+                .par_iter_mut()
+                .zip(self.scheduler.active_flags().par_iter())
+                .enumerate()
+                .filter(|(_, (_, &active))| active)
+                .map(|(i, (c, _))| (i, transition(i, c), take_errors(&mut **c)))
+                .collect();
+            for (i, t_next, mut errors) in t_nexts {
+                self.scheduler.schedule(i, t_next);
+                self.errors.append(&mut errors);
+            }
+        }
+        #[cfg(not(feature = "par_transition"))]
+        for k in 0..self.scheduler.active().len() {
+            let i = self.scheduler.active()[k];
+            let t_next = transition(i, &mut self.components[i]);
+            self.scheduler.schedule(i, t_next);
+            self.errors
+                .append(&mut take_errors(&mut *self.components[i]));
+        }
+        self.scheduler.clear_active();
+        // structural changes requested during this step are applied before rescheduling
+        self.apply_changes(t);
+        if self.scheduler.is_bloated() {
+            self.reset_scheduler();
+        }
+        let t_next = self.next_time();
+        self.set_sim_t(t, t_next);
+    }
+}
+
+/// Returns the errors of the invalid structural changes of a model that have not been reported yet.
+/// Atomic models do not have structural changes.
+#[inline]
+fn take_errors<T: Time>(model: &mut dyn Simulator<T>) -> Vec<Error> {
+    model
+        .as_coupled_mut()
+        .map(|coupled| std::mem::take(&mut coupled.errors))
+        .unwrap_or_default()
+}
+
+/// Calls `f` for a model and all its subcomponents (depth-first), together with their full path.
+/// The full path of a subcomponent is the full path of its parent followed by a dot and its name.
+pub(crate) fn visit_mut<T: Time>(
+    model: &mut dyn Simulator<T>,
+    path: &str,
+    f: &mut dyn FnMut(&str, &mut dyn Simulator<T>),
+) {
+    f(path, model);
+    if let Some(coupled) = model.as_coupled_mut() {
+        for component in coupled.components.iter_mut() {
+            let child_path = format!("{path}.{}", component.get_name());
+            visit_mut(&mut **component, &child_path, f);
+        }
+    }
+}
+
 /// Root coordinator for sequential simulations of DEVS models.
-pub struct RootCoordinator<T>(T);
+///
+/// Simulations can be run at once with [`RootCoordinator::simulate`].
+/// Alternatively, they can be driven step by step with [`RootCoordinator::start`],
+/// [`RootCoordinator::step`], [`RootCoordinator::simulate_until`], and [`RootCoordinator::finish`].
+///
+/// Messages sent through the output ports of the model can be observed with
+/// [`RootCoordinator::on_output`] and [`RootCoordinator::collect_output`].
+///
+/// `M` is the type of the model under simulation, and `T` is the type used for
+/// representing simulation times. By default, it is [`f64`].
+pub struct RootCoordinator<M, T = f64> {
+    /// DEVS model under simulation.
+    model: M,
+    /// Current simulation time.
+    t: T,
+    /// Time of the next simulation event.
+    t_next: T,
+    /// Callbacks for the output ports of the model.
+    callbacks: Vec<Box<dyn OutputListener<T>>>,
+    /// Message histories of the output ports of the model. Keys are the port names.
+    histories: HashMap<String, Box<dyn OutputListener<T>>>,
+}
 
-impl<T: Simulator> RootCoordinator<T> {
+impl<T: Time, M: Simulator<T>> RootCoordinator<M, T> {
     /// Creates a new root coordinator from a DEVS-compliant model.
-    pub fn new(model: T) -> Self {
-        Self(model)
+    /// In debug builds, coupled models are validated (see [`Coupled::validate`]),
+    /// and this method panics with the list of issues found, if any.
+    /// Release builds skip the validation. Use [`RootCoordinator::validated`]
+    /// to get the issues instead of panicking.
+    pub fn new(model: M) -> Self {
+        #[cfg(debug_assertions)]
+        if let Some(coupled) = model.as_coupled() {
+            assert_valid(coupled);
+        }
+        Self::unvalidated(model)
     }
 
-    /// Runs a simulation for a given period of time.
-    pub fn simulate(&mut self, t_end: f64) {
-        let mut t_next = self.start(0.);
-        while t_next < t_end {
-            self.collection(t_next);
-            t_next = self.transition(t_next);
+    /// Creates a new root coordinator from a DEVS-compliant model if it passes validation.
+    /// If the model is a coupled model, it returns the issues found by [`Coupled::validate`], if any.
+    pub fn validated(model: M) -> std::result::Result<Self, Vec<Issue>> {
+        if let Some(coupled) = model.as_coupled() {
+            let issues = coupled.validate();
+            if !issues.is_empty() {
+                return Err(issues);
+            }
         }
-        self.stop(t_next);
+        Ok(Self::unvalidated(model))
+    }
+
+    /// Creates a new root coordinator from a DEVS-compliant model without validating it,
+    /// not even in debug builds. It is intended for models that leave some ports unconnected
+    /// on purpose (e.g., the DEVStone benchmark models).
+    pub fn unvalidated(model: M) -> Self {
+        #[allow(unused_mut)]
+        let mut coordinator = Self {
+            model,
+            t: T::ZERO,
+            t_next: T::INFINITY,
+            callbacks: Vec::new(),
+            histories: HashMap::new(),
+        };
+        #[cfg(feature = "rand")]
+        coordinator.set_seed(0);
+        coordinator
+    }
+
+    /// Returns a reference to an output port of the model under simulation.
+    /// It panics if the port does not exist or if it is not of type `V`.
+    fn get_out_port<V: DynRef + Clone>(&self, port_name: &str) -> Arc<dyn Port> {
+        let port = self
+            .model
+            .get_component()
+            .get_out_port(port_name)
+            .expect("port does not exist");
+        if !port.is_type::<V>() {
+            panic!("port is not of the type provided");
+        }
+        port
+    }
+
+    /// Registers a function that is called for every message sent through an output port
+    /// of the model under simulation. The function receives the simulation time and the message.
+    /// This method panics if the port does not exist or if it is not of type `V`.
+    pub fn on_output<V, F>(&mut self, port_name: &str, f: F)
+    where
+        V: DynRef + Clone,
+        F: FnMut(T, &V) + 'static,
+    {
+        let port = self.get_out_port::<V>(port_name);
+        self.callbacks.push(Box::new(OutputCallback::new(port, f)));
+    }
+
+    /// Starts storing all the messages sent through an output port of the model under simulation.
+    /// Messages can be read with [`RootCoordinator::get_output`].
+    /// This method panics if the port does not exist or if it is not of type `V`.
+    pub fn collect_output<V: DynRef + Clone>(&mut self, port_name: &str) {
+        let port = self.get_out_port::<V>(port_name);
+        let history = Box::new(OutputHistory::<T, V>::new(port));
+        self.histories.insert(port_name.to_string(), history);
+    }
+
+    /// Returns all the messages sent through an output port of the model under simulation,
+    /// together with the time when they were sent. The port must have been registered with
+    /// [`RootCoordinator::collect_output`]. Otherwise, it returns [`None`].
+    /// It also returns [`None`] if the port is not of type `V`.
+    pub fn get_output<V: DynRef + Clone>(&self, port_name: &str) -> Option<&[(T, V)]> {
+        let history = self.histories.get(port_name)?;
+        let history = history.as_any().downcast_ref::<OutputHistory<T, V>>()?;
+        Some(&history.values)
+    }
+
+    /// Sets the master seed of the simulation. By default, it is 0.
+    ///
+    /// Every component of the model gets its own random number stream
+    /// (see [`Component::rng`]), which is derived from the master seed and the full path
+    /// of the component (e.g., `root.coupled.atomic`). Thus, the random numbers of a component
+    /// do not change when other components are added to or removed from the model.
+    /// Streams are reset every time the master seed is set.
+    #[cfg(feature = "rand")]
+    pub fn set_seed(&mut self, seed: u64) {
+        let path = self.model.get_name().to_string();
+        visit_mut(&mut self.model, &path, &mut |path, model| {
+            let stream = Some(crate::rng::RngStream::new(seed, path));
+            model.get_component_mut().set_rng_stream(stream)
+        });
+    }
+
+    /// Sets the simulation observer of the model and all its subcomponents.
+    pub fn set_observer(&mut self, observer: Arc<dyn SimObserver<T>>) {
+        self.install_observer(Some(observer));
+    }
+
+    /// Removes the simulation observer of the model and all its subcomponents.
+    pub fn remove_observer(&mut self) {
+        self.install_observer(None);
+    }
+
+    /// Sets (or removes) the simulation observer of the model and all its subcomponents.
+    fn install_observer(&mut self, observer: Option<Arc<dyn SimObserver<T>>>) {
+        let path = self.model.get_name().to_string();
+        visit_mut(&mut self.model, &path, &mut |path, model| {
+            model
+                .get_component_mut()
+                .set_observer(path, observer.clone())
+        });
+    }
+
+    /// Executes the collection phase of the model and notifies all the output listeners.
+    fn execute_collection(&mut self, t: T) {
+        self.model.collection(t);
+        let listeners = self.callbacks.iter_mut().chain(self.histories.values_mut());
+        for listener in listeners {
+            // Safety: root coordinator reading output messages after the collection phase
+            unsafe { listener.notify(t) };
+        }
+    }
+
+    /// Returns the current simulation time.
+    #[inline]
+    pub fn get_time(&self) -> T {
+        self.t
+    }
+
+    /// Returns the time of the next simulation event.
+    /// If there are no more events, it returns infinity.
+    #[inline]
+    pub fn next_event_time(&self) -> T {
+        self.t_next
+    }
+
+    /// Starts the simulation, setting the current simulation time to 0.
+    /// It must be called before stepping the simulation.
+    pub fn start(&mut self) {
+        self.t = T::ZERO;
+        self.t_next = self.model.start(self.t);
+    }
+
+    /// Executes the next simulation event and returns its time.
+    /// If there are no more events, it does nothing and returns [`None`].
+    /// This method panics if any structural change requested through a [`StructureHandle`](crate::modeling::StructureHandle)
+    /// is not valid (see [`RootCoordinator::try_step`]).
+    pub fn step(&mut self) -> Option<T> {
+        self.try_step().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Executes the next simulation event and returns its time.
+    /// If there are no more events, it does nothing and returns `Ok(None)`.
+    ///
+    /// Structural changes requested through [`StructureHandle`](crate::modeling::StructureHandle)s that are not valid
+    /// (e.g., adding a coupling from a component that does not exist) are discarded,
+    /// and the error of the first of them is returned. The remaining changes are applied,
+    /// so the simulation can be resumed.
+    pub fn try_step(&mut self) -> Result<Option<T>> {
+        if self.t_next == T::INFINITY {
+            return Ok(None);
+        }
+        let t = self.t_next;
+        self.execute_collection(t);
+        self.t_next = self.model.transition(t);
+        self.t = t;
+        self.check_changes()?;
+        Ok(Some(t))
+    }
+
+    /// Returns the error of the first invalid structural change of the last simulation step (if any).
+    fn check_changes(&mut self) -> Result<()> {
+        match take_errors(&mut self.model).into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Executes all the simulation events that occur before `t_end`.
+    /// Then, it advances the current simulation time up to `t_end`.
+    /// The simulation can be resumed later on.
+    /// This method panics if any structural change requested through a [`StructureHandle`](crate::modeling::StructureHandle)
+    /// is not valid (see [`RootCoordinator::try_step`]).
+    pub fn simulate_until(&mut self, t_end: T) {
+        self.try_simulate_until(t_end)
+            .unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Executes all the simulation events that occur before `t_end`.
+    /// Then, it advances the current simulation time up to `t_end`.
+    /// If any structural change is not valid, it stops at the time of the step that requested it
+    /// and returns an error (see [`RootCoordinator::try_step`]).
+    pub fn try_simulate_until(&mut self, t_end: T) -> Result<()> {
+        while self.t_next < t_end {
+            self.try_step()?;
+        }
+        if t_end > self.t {
+            self.t = t_end;
+        }
+        Ok(())
+    }
+
+    /// Injects values into an input port of the model under simulation at time `t`.
+    /// First, it executes all the simulation events that occur before `t`.
+    /// Then, it triggers the external (or confluent) transition of the model.
+    /// This method panics if:
+    /// - `t` is less than the current simulation time.
+    /// - the port does not exist.
+    /// - the port is not of type `V`.
+    /// - any structural change requested through a [`StructureHandle`](crate::modeling::StructureHandle) is not valid.
+    pub fn inject<V: DynRef + Clone>(&mut self, port_name: &str, values: &[V], t: T) {
+        if t < self.t {
+            panic!("injection time is less than the current simulation time");
+        }
+        self.simulate_until(t);
+        let port = self
+            .model
+            .get_component()
+            .get_in_port(port_name)
+            .expect("port does not exist");
+        // Safety: root coordinator injecting messages between simulation steps
+        if !unsafe { port.inject(values) } {
+            panic!("port is not compatible with the values provided");
+        }
+        if t >= self.t_next {
+            self.execute_collection(t);
+        }
+        self.t_next = self.model.transition(t);
+        self.check_changes().unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Finishes the simulation at the current simulation time.
+    pub fn finish(&mut self) {
+        self.model.stop(self.t);
+        self.t_next = T::INFINITY;
+    }
+
+    /// Runs a simulation for a given period of time.
+    /// It is equivalent to calling [`RootCoordinator::start`], [`RootCoordinator::simulate_until`],
+    /// and [`RootCoordinator::finish`]. Thus, events scheduled at `t_end` or later are not executed,
+    /// and the models are stopped at `t_end`. Note that, up to version 0.3.0, the simulation was
+    /// stopped at the time of the first event not executed (which could be infinity).
+    pub fn simulate(&mut self, t_end: T) {
+        self.start();
+        self.simulate_until(t_end);
+        self.finish();
+    }
+
+    /// Saves a checkpoint of the simulation in JSON format.
+    /// The checkpoint contains the current simulation time, the last and next times of
+    /// all the components, and the state of all the atomic models that implement
+    /// [`crate::modeling::Snapshot`]. With the `rand` feature, it also contains the position
+    /// of the random number streams of all the components, so restored simulations draw the same
+    /// numbers as the saved one. Port bags are always empty between simulation steps,
+    /// so checkpoints do not contain any message. Output listeners are not saved either.
+    #[cfg(feature = "serde")]
+    pub fn save<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
+        let checkpoint = checkpoint::Checkpoint {
+            t: self.t,
+            t_next: self.t_next,
+            model: checkpoint::ModelState::save(&self.model)?,
+        };
+        serde_json::to_writer(writer, &checkpoint)?;
+        Ok(())
+    }
+
+    /// Restores a simulation from a checkpoint created with [`RootCoordinator::save`].
+    /// The model must have the same structure as the model used for creating the checkpoint.
+    /// Otherwise, it returns an error and the simulation is not modified.
+    /// Then, the simulation can be resumed without calling [`RootCoordinator::start`].
+    /// Atomic models that do not implement [`crate::modeling::Snapshot`] keep their current state.
+    #[cfg(feature = "serde")]
+    pub fn restore<R: std::io::Read>(&mut self, reader: R) -> std::io::Result<()> {
+        let checkpoint: checkpoint::Checkpoint<T> = serde_json::from_reader(reader)?;
+        checkpoint.model.restore(&mut self.model)?;
+        self.t = checkpoint.t;
+        self.t_next = checkpoint.t_next;
+        Ok(())
     }
 }
 
-impl<T> Deref for RootCoordinator<T> {
-    type Target = T;
+impl<M, T> Deref for RootCoordinator<M, T> {
+    type Target = M;
+
+    fn deref(&self) -> &Self::Target {
+        &self.model
+    }
+}
+
+impl<M, T> DerefMut for RootCoordinator<M, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.model
+    }
+}
+
+/// Panics with the list of issues found by [`Coupled::validate`], if any.
+#[cfg(debug_assertions)]
+fn assert_valid<T: Time>(coupled: &Coupled<T>) {
+    let issues = coupled.validate();
+    if !issues.is_empty() {
+        let issues: Vec<_> = issues.iter().map(|issue| format!("\n- {issue}")).collect();
+        panic!("model is not valid:{}", issues.concat());
+    }
+}
+
+/// Root coordinator for sequential simulations of flattened DEVS models.
+/// The hierarchy of the coupled model is collapsed using [`Coupled::flatten`].
+/// Thus, messages go directly from atomic to atomic models.
+pub struct FlatCoordinator<T: Time = f64>(RootCoordinator<Coupled<T>, T>);
+
+impl<T: Time> FlatCoordinator<T> {
+    /// Creates a new root coordinator from a flattened version of a coupled DEVS model.
+    /// As with [`RootCoordinator::new`], debug builds validate the original coupled model
+    /// and panic if any issue is found.
+    pub fn new(model: Coupled<T>) -> Self {
+        #[cfg(debug_assertions)]
+        assert_valid(&model);
+        Self::unvalidated(model)
+    }
+
+    /// Creates a new root coordinator from a flattened version of a coupled DEVS model
+    /// without validating it (see [`RootCoordinator::unvalidated`]).
+    pub fn unvalidated(model: Coupled<T>) -> Self {
+        Self(RootCoordinator::unvalidated(model.flatten()))
+    }
+}
+
+impl<T: Time> Deref for FlatCoordinator<T> {
+    type Target = RootCoordinator<Coupled<T>, T>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl<T> DerefMut for RootCoordinator<T> {
+impl<T: Time> DerefMut for FlatCoordinator<T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{InPort, OutPort};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    #[test]
+    fn test_step() {
+        let mut simulator = RootCoordinator::new(create_model());
+        assert_eq!(None, simulator.step());
+
+        simulator.start();
+        assert_eq!(0., simulator.get_time());
+        assert_eq!(2., simulator.next_event_time());
+        assert_eq!(Some(2.), simulator.step());
+        assert_eq!(2., simulator.get_time());
+        assert_eq!(2., simulator.get_t_last());
+        assert_eq!(4., simulator.next_event_time());
+
+        simulator.simulate_until(7.);
+        assert_eq!(7., simulator.get_time());
+        assert_eq!(6., simulator.get_t_last());
+        assert_eq!(8., simulator.next_event_time());
+        // resuming the simulation does not execute events twice
+        simulator.simulate_until(8.);
+        assert_eq!(6., simulator.get_t_last());
+        assert_eq!(Some(8.), simulator.step());
+        assert_eq!(8., simulator.get_t_last());
+
+        simulator.finish();
+        assert_eq!(8., simulator.get_t_last());
+        assert_eq!(f64::INFINITY, simulator.get_t_next());
+        assert_eq!(None, simulator.step());
+    }
+
+    #[test]
+    fn test_inject() {
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.start();
+        simulator.inject("input", &[true], 3.);
+        assert_eq!(3., simulator.get_time());
+        assert_eq!(3., simulator.get_t_last());
+        assert_eq!(f64::INFINITY, simulator.next_event_time());
+        assert_eq!(None, simulator.step());
+
+        // injecting at the time of the next event triggers a confluent transition
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.start();
+        simulator.inject::<bool>("input", &[], 3.);
+        assert_eq!(2., simulator.get_t_last());
+        assert_eq!(4., simulator.next_event_time());
+        simulator.inject("input", &[true], 4.);
+        assert_eq!(4., simulator.get_t_last());
+        assert_eq!(f64::INFINITY, simulator.next_event_time());
+    }
+
+    #[test]
+    fn test_output() {
+        let mut simulator = RootCoordinator::new(create_model());
+        let count = Rc::new(RefCell::new(0));
+        let count_clone = count.clone();
+        simulator.on_output("output", move |_, _: &usize| *count_clone.borrow_mut() += 1);
+        simulator.collect_output::<usize>("output");
+        assert!(simulator.get_output::<bool>("output").is_none());
+
+        simulator.start();
+        simulator.simulate_until(7.);
+        let expected = [(2., 0), (4., 1), (6., 2)];
+        assert_eq!(&expected, simulator.get_output::<usize>("output").unwrap());
+        assert_eq!(3, *count.borrow());
+        // the injection triggers a confluent transition, so the generator sends a message first
+        simulator.inject("input", &[true], 8.);
+        simulator.simulate_until(f64::INFINITY);
+        let expected = [(2., 0), (4., 1), (6., 2), (8., 3)];
+        assert_eq!(&expected, simulator.get_output::<usize>("output").unwrap());
+        assert_eq!(4, *count.borrow());
+    }
+
+    #[test]
+    fn test_validated() {
+        assert!(RootCoordinator::validated(create_model()).is_ok());
+
+        let mut coupled = create_model();
+        coupled.add_component(Box::new(Generator::new("isolated", 1.)));
+        let issues = RootCoordinator::validated(coupled).err().unwrap();
+        let expected = Issue::IsolatedComponent {
+            path: "root.isolated".to_string(),
+        };
+        assert_eq!(vec![expected], issues);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "model is not valid:\n- component root.isolated has no couplings")]
+    fn test_new_invalid() {
+        let mut coupled = create_model();
+        coupled.add_component(Box::new(Generator::new("isolated", 1.)));
+        RootCoordinator::new(coupled);
+    }
+
+    #[test]
+    fn test_unvalidated() {
+        let mut coupled = create_model();
+        coupled.add_component(Box::new(Generator::new("isolated", 1.)));
+        let mut simulator = FlatCoordinator::unvalidated(coupled);
+        simulator.collect_output::<usize>("output");
+        simulator.simulate(3.);
+        assert_eq!(&[(2., 0)], simulator.get_output::<usize>("output").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "port is not compatible with the values provided")]
+    fn test_inject_incompatible() {
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.start();
+        simulator.inject("input", &[1_usize], 1.);
+    }
+}
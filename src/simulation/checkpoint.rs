@@ -0,0 +1,450 @@
+use super::Simulator;
+use crate::modeling::Time;
+#[cfg(feature = "rand")]
+use crate::rng::{RngState, RngStream};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::io;
+
+/// Checkpoint of a running simulation.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "T: Time")]
+pub(crate) struct Checkpoint<T> {
+    /// Current simulation time.
+    #[serde(with = "time")]
+    pub(crate) t: T,
+    /// Time of the next simulation event.
+    #[serde(with = "time")]
+    pub(crate) t_next: T,
+    /// State of the model under simulation.
+    pub(crate) model: ModelState<T>,
+}
+
+/// Saved state of a DEVS model.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "T: Time")]
+pub(crate) struct ModelState<T> {
+    /// Name of the model. It is used for checking that the checkpoint matches the model.
+    name: String,
+    /// Time of the last state transition of the model.
+    #[serde(with = "time")]
+    t_last: T,
+    /// Time of the next state transition of the model.
+    #[serde(with = "time")]
+    t_next: T,
+    /// State of atomic models that implement [`crate::modeling::Snapshot`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    state: Option<Value>,
+    /// State of the random number stream of the model.
+    #[cfg(feature = "rand")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rng: Option<RngState>,
+    /// Saved state of the subcomponents of coupled models.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    components: Vec<ModelState<T>>,
+}
+
+impl<T: Time> ModelState<T> {
+    /// Saves the state of a model and all its subcomponents.
+    /// Note that port bags are empty between simulation steps, so they are not saved.
+    pub(crate) fn save(model: &dyn Simulator<T>) -> serde_json::Result<Self> {
+        let state = match model.as_snapshot() {
+            Some(snapshot) => Some(snapshot.save_value()?),
+            None => None,
+        };
+        let components = match model.as_coupled() {
+            Some(coupled) => coupled
+                .components
+                .iter()
+                .map(|c| Self::save(&**c))
+                .collect::<serde_json::Result<_>>()?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            name: model.get_name().to_string(),
+            t_last: model.get_t_last(),
+            t_next: model.get_t_next(),
+            state,
+            #[cfg(feature = "rand")]
+            rng: model.get_component().get_rng_stream().map(RngStream::save),
+            components,
+        })
+    }
+
+    /// Restores the state of a model and all its subcomponents.
+    /// It returns an error if the saved state does not match the structure of the model.
+    /// In that case, the model is not modified.
+    pub(crate) fn restore(self, model: &mut dyn Simulator<T>) -> io::Result<()> {
+        self.check(model)?;
+        self.apply(model)
+    }
+
+    /// Checks that the saved state matches the structure of a model and all its subcomponents.
+    /// It does not modify the model.
+    fn check(&self, model: &dyn Simulator<T>) -> io::Result<()> {
+        if self.name != model.get_name() {
+            return Err(invalid_data(format!(
+                "checkpoint of model {} does not match model {}",
+                self.name,
+                model.get_name()
+            )));
+        }
+        if let (Some(state), Some(snapshot)) = (&self.state, model.as_snapshot()) {
+            snapshot.check_value(state)?;
+        }
+        let components = model.as_coupled().map_or(&[][..], |c| &c.components);
+        if self.components.len() != components.len() {
+            return Err(invalid_data(format!(
+                "checkpoint of model {} does not match its number of components",
+                self.name
+            )));
+        }
+        for (state, component) in self.components.iter().zip(components) {
+            state.check(&**component)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the saved state of a model and all its subcomponents.
+    /// The saved state must have been checked with [`ModelState::check`].
+    fn apply(self, model: &mut dyn Simulator<T>) -> io::Result<()> {
+        model.clear();
+        model.set_sim_t(self.t_last, self.t_next);
+        if let (Some(state), Some(snapshot)) = (self.state, model.as_snapshot_mut()) {
+            snapshot.restore_value(state)?;
+        }
+        #[cfg(feature = "rand")]
+        if let Some(rng) = self.rng {
+            let stream = Some(RngStream::restore(rng));
+            model.get_component_mut().set_rng_stream(stream);
+        }
+        if let Some(coupled) = model.as_coupled_mut() {
+            for (state, component) in self.components.into_iter().zip(&mut coupled.components) {
+                state.apply(&mut **component)?;
+            }
+            coupled.reset_scheduler();
+            #[cfg(feature = "par_couplings")]
+            {
+                coupled.build_par_eics();
+                coupled.build_par_xxcs();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Creates a new I/O error for checkpoints that do not match the model.
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// (De)serialization of simulation times. Infinity is represented as `null`, as JSON does not support it.
+mod time {
+    use super::*;
+
+    pub(super) fn serialize<T: Time, S: Serializer>(
+        t: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *t == T::INFINITY {
+            true => serializer.serialize_none(),
+            false => serializer.serialize_some(t),
+        }
+    }
+
+    pub(super) fn deserialize<'de, T: Time, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        Ok(Option::<T>::deserialize(deserializer)?.unwrap_or(T::INFINITY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modeling::{
+        Atomic, Component, Coupled, DynSnapshot, HasComponent, InPort, InputCtx, OutPort,
+        OutputCtx, Snapshot,
+    };
+    #[cfg(feature = "rand")]
+    use crate::rng::Rng;
+    use crate::simulation::{RootCoordinator, Simulator};
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+
+        fn as_snapshot(&self) -> Option<&dyn DynSnapshot> {
+            Some(self)
+        }
+
+        fn as_snapshot_mut(&mut self) -> Option<&mut dyn DynSnapshot> {
+            Some(self)
+        }
+    }
+
+    impl Snapshot for Generator {
+        type State = (Option<f64>, usize);
+
+        fn save_state(&self) -> Self::State {
+            let sigma = Some(self.sigma).filter(|sigma| sigma.is_finite());
+            (sigma, self.count)
+        }
+
+        fn restore_state(&mut self, (sigma, count): Self::State) {
+            self.sigma = sigma.unwrap_or(f64::INFINITY);
+            self.count = count;
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    /// Atomic model that sends a random number every second.
+    #[cfg(feature = "rand")]
+    struct Noise {
+        component: Component,
+        value: u64,
+        output: OutPort<u64>,
+    }
+
+    #[cfg(feature = "rand")]
+    impl Noise {
+        fn new(name: &str) -> Self {
+            let mut component = Component::new(name);
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                value: 0,
+                output,
+            }
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    impl HasComponent for Noise {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    impl Atomic for Noise {
+        fn start(&mut self) {
+            self.value = self.component.rng().gen();
+        }
+
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.value);
+        }
+
+        fn delta_int(&mut self) {
+            self.value = self.component.rng().gen();
+        }
+
+        fn delta_ext(&mut self, _e: f64, _input: &InputCtx) {}
+
+        fn ta(&self) -> f64 {
+            1.
+        }
+
+        fn as_snapshot(&self) -> Option<&dyn DynSnapshot> {
+            Some(self)
+        }
+
+        fn as_snapshot_mut(&mut self) -> Option<&mut dyn DynSnapshot> {
+            Some(self)
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    impl Snapshot for Noise {
+        type State = u64;
+
+        fn save_state(&self) -> Self::State {
+            self.value
+        }
+
+        fn restore_state(&mut self, value: Self::State) {
+            self.value = value;
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.collect_output::<usize>("output");
+        simulator.start();
+        simulator.simulate_until(5.);
+        let mut checkpoint = Vec::new();
+        simulator.save(&mut checkpoint).unwrap();
+
+        let mut restored = RootCoordinator::new(create_model());
+        restored.collect_output::<usize>("output");
+        restored.restore(checkpoint.as_slice()).unwrap();
+        assert_eq!(5., restored.get_time());
+        assert_eq!(6., restored.next_event_time());
+        assert_eq!(4., restored.get_t_last());
+
+        for simulator in [&mut simulator, &mut restored] {
+            simulator.simulate_until(9.);
+            simulator.inject("input", &[true], 9.);
+            simulator.simulate_until(20.);
+        }
+        let expected = [(6., 2), (8., 3)];
+        let output = restored.get_output::<usize>("output").unwrap();
+        assert_eq!(&expected, output);
+        assert_eq!(
+            &expected,
+            &simulator.get_output::<usize>("output").unwrap()[2..]
+        );
+        assert_eq!(f64::INFINITY, restored.next_event_time());
+
+        let mut other = RootCoordinator::new(Coupled::<f64>::new("other"));
+        let err = other.restore(checkpoint.as_slice()).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+
+        // Checkpoints that do not match the model do not modify it
+        let mut coupled = create_model();
+        coupled.add_component(Box::new(Generator::new("other", 1.)));
+        coupled.add_eic("input", "other", "input");
+        coupled.add_eoc("other", "output", "output");
+        let mut other = RootCoordinator::new(coupled);
+        other.start();
+        let err = other.restore(checkpoint.as_slice()).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert_eq!((0., 1.), (other.get_t_last(), other.get_t_next()));
+
+        let mut invalid: serde_json::Value = serde_json::from_slice(&checkpoint).unwrap();
+        invalid["model"]["components"][0]["state"] = "invalid".into();
+        let mut other = RootCoordinator::new(create_model());
+        other.start();
+        let err = other.restore(invalid.to_string().as_bytes()).unwrap_err();
+        assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+        assert_eq!((0., 2.), (other.get_t_last(), other.get_t_next()));
+    }
+
+    #[test]
+    fn test_checkpoint_passive() {
+        // Infinite times are saved as null
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.start();
+        simulator.inject("input", &[true], 1.);
+        let mut checkpoint = Vec::new();
+        simulator.save(&mut checkpoint).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&checkpoint).unwrap();
+        assert!(value["t_next"].is_null());
+        assert_eq!(
+            serde_json::json!([null, 0]),
+            value["model"]["components"][0]["state"]
+        );
+
+        let mut restored = RootCoordinator::new(create_model());
+        restored.collect_output::<usize>("output");
+        restored.restore(checkpoint.as_slice()).unwrap();
+        assert_eq!(1., restored.get_time());
+        assert_eq!(f64::INFINITY, restored.next_event_time());
+        restored.simulate_until(10.);
+        assert!(restored.get_output::<usize>("output").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_checkpoint_rng() {
+        let create_model = || {
+            let mut coupled = Coupled::new("root");
+            coupled.add_out_port::<u64>("output");
+            coupled.add_component(Box::new(Noise::new("noise")));
+            coupled.add_eoc("noise", "output", "output");
+            coupled
+        };
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.set_seed(42);
+        simulator.collect_output::<u64>("output");
+        simulator.start();
+        simulator.simulate_until(3.5);
+        let mut checkpoint = Vec::new();
+        simulator.save(&mut checkpoint).unwrap();
+
+        // The restored simulation continues the random number streams of the saved one,
+        // even if its master seed was different
+        let mut restored = RootCoordinator::new(create_model());
+        restored.collect_output::<u64>("output");
+        restored.restore(checkpoint.as_slice()).unwrap();
+        for simulator in [&mut simulator, &mut restored] {
+            simulator.simulate_until(10.5);
+        }
+        let output = restored.get_output::<u64>("output").unwrap();
+        assert_eq!(7, output.len());
+        assert_eq!(output, &simulator.get_output::<u64>("output").unwrap()[3..]);
+    }
+}
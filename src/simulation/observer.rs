@@ -0,0 +1,254 @@
+use crate::modeling::Time;
+use crate::DynRef;
+use std::fmt::Debug;
+
+/// Description of a coupling between two ports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CouplingInfo<'a> {
+    /// Name of the sending component. It is [`None`] for the coupled model itself (i.e., EICs).
+    pub component_from: Option<&'a str>,
+    /// Name of the sending port.
+    pub port_from: &'a str,
+    /// Name of the receiving component. It is [`None`] for the coupled model itself (i.e., EOCs).
+    pub component_to: Option<&'a str>,
+    /// Name of the receiving port.
+    pub port_to: &'a str,
+}
+
+/// Interface for observing the simulation of DEVS models.
+/// Observers are set with [`super::RootCoordinator::set_observer`].
+///
+/// Every method receives the full path of the model (e.g., `root.subcoupled.atomic`)
+/// and the current simulation time. By default, methods do nothing.
+/// Note that, if parallel features are activated, observers may be called from different threads.
+/// `T` is the type used for representing simulation times. By default, it is [`f64`].
+pub trait SimObserver<T: Time = f64>: DynRef {
+    /// Called when a model (either atomic or coupled) starts the simulation.
+    #[inline]
+    fn on_start(&self, _path: &str, _t: T) {}
+
+    /// Called after executing the output function of an atomic model.
+    #[inline]
+    fn on_lambda(&self, _path: &str, _t: T) {}
+
+    /// Called for every message sent by an atomic model, after calling [`SimObserver::on_lambda`].
+    /// Messages of ports that are not traced are represented by the name of their type
+    /// (see [`Component::add_traced_out_port`](crate::modeling::Component::add_traced_out_port)).
+    #[inline]
+    fn on_output(&self, _path: &str, _t: T, _port: &str, _value: &dyn Debug) {}
+
+    /// Called for every message received by an atomic model, before calling
+    /// [`SimObserver::on_delta_ext`] or [`SimObserver::on_delta_conf`].
+    /// Messages of ports that are not traced are represented by the name of their type
+    /// (see [`Component::add_traced_in_port`](crate::modeling::Component::add_traced_in_port)).
+    #[inline]
+    fn on_input(&self, _path: &str, _t: T, _port: &str, _value: &dyn Debug) {}
+
+    /// Called after executing the internal transition function of an atomic model.
+    #[inline]
+    fn on_delta_int(&self, _path: &str, _t: T) {}
+
+    /// Called after executing the external transition function of an atomic model.
+    /// `e` corresponds to the elapsed time since the last state transition of the model.
+    #[inline]
+    fn on_delta_ext(&self, _path: &str, _t: T, _e: T) {}
+
+    /// Called after executing the confluent transition function of an atomic model.
+    #[inline]
+    fn on_delta_conf(&self, _path: &str, _t: T) {}
+
+    /// Called after a coupled model propagates messages through one of its couplings.
+    #[inline]
+    fn on_propagate(&self, _path: &str, _t: T, _coupling: &CouplingInfo) {}
+
+    /// Called when a model (either atomic or coupled) stops the simulation.
+    #[inline]
+    fn on_stop(&self, _path: &str, _t: T) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{
+        Atomic, Component, Coupled, HasComponent, InPort, InputCtx, OutPort, OutputCtx,
+    };
+    use crate::simulation::RootCoordinator;
+    use std::sync::{Arc, Mutex};
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64) -> Self {
+            let mut component = Component::new(name);
+            let input = component.add_in_port("input");
+            let output = component.add_out_port("output");
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model() -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2.)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    /// Simulation observer that records all the events.
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl Recorder {
+        fn record(&self, event: String) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl SimObserver for Recorder {
+        fn on_start(&self, path: &str, t: f64) {
+            self.record(format!("{t} start {path}"));
+        }
+
+        fn on_lambda(&self, path: &str, t: f64) {
+            self.record(format!("{t} lambda {path}"));
+        }
+
+        fn on_output(&self, path: &str, t: f64, port: &str, value: &dyn Debug) {
+            self.record(format!("{t} output {path}.{port} {value:?}"));
+        }
+
+        fn on_input(&self, path: &str, t: f64, port: &str, value: &dyn Debug) {
+            self.record(format!("{t} input {path}.{port} {value:?}"));
+        }
+
+        fn on_delta_int(&self, path: &str, t: f64) {
+            self.record(format!("{t} delta_int {path}"));
+        }
+
+        fn on_delta_ext(&self, path: &str, t: f64, e: f64) {
+            self.record(format!("{t} delta_ext {path} {e}"));
+        }
+
+        fn on_delta_conf(&self, path: &str, t: f64) {
+            self.record(format!("{t} delta_conf {path}"));
+        }
+
+        fn on_propagate(&self, path: &str, t: f64, coupling: &CouplingInfo) {
+            let from = coupling.component_from.unwrap_or(path);
+            let to = coupling.component_to.unwrap_or(path);
+            let (port_from, port_to) = (coupling.port_from, coupling.port_to);
+            self.record(format!("{t} propagate {from}.{port_from} {to}.{port_to}"));
+        }
+
+        fn on_stop(&self, path: &str, t: f64) {
+            self.record(format!("{t} stop {path}"));
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let recorder = Arc::new(Recorder::default());
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.set_observer(recorder.clone());
+        simulator.start();
+        simulator.step();
+        simulator.inject("input", &[true], 3.);
+        simulator.finish();
+        let expected = [
+            "0 start root.generator",
+            "0 start root",
+            "2 lambda root.generator",
+            "2 output root.generator.output <usize>",
+            "2 propagate generator.output root.output",
+            "2 delta_int root.generator",
+            "3 propagate root.input generator.input",
+            "3 input root.generator.input <bool>",
+            "3 delta_ext root.generator 1",
+            "3 stop root.generator",
+            "3 stop root",
+        ];
+        assert_eq!(&expected[..], &recorder.0.lock().unwrap()[..]);
+
+        simulator.remove_observer();
+        simulator.start();
+        assert_eq!(expected.len(), recorder.0.lock().unwrap().len());
+    }
+
+    #[test]
+    fn test_observer_delta_conf() {
+        let recorder = Arc::new(Recorder::default());
+        let mut simulator = RootCoordinator::new(create_model());
+        simulator.set_observer(recorder.clone());
+        simulator.start();
+        simulator.inject("input", &[true], 2.);
+        let expected = [
+            "2 lambda root.generator",
+            "2 output root.generator.output <usize>",
+            "2 propagate generator.output root.output",
+            "2 propagate root.input generator.input",
+            "2 input root.generator.input <bool>",
+            "2 delta_conf root.generator",
+        ];
+        assert_eq!(&expected[..], &recorder.0.lock().unwrap()[2..]);
+        // observers can be replaced during the simulation
+        let other = Arc::new(Recorder::default());
+        simulator.set_observer(other.clone());
+        simulator.finish();
+        assert_eq!(8, recorder.0.lock().unwrap().len());
+        let expected = ["2 stop root.generator", "2 stop root"];
+        assert_eq!(&expected[..], &other.0.lock().unwrap()[..]);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::modeling::port::Port;
+use crate::modeling::Time;
+use crate::DynRef;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Listener of an output port of the model under simulation.
+pub(crate) trait OutputListener<T> {
+    /// Listener-to-any conversion.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Notifies the listener that the model may have produced output messages at time `t`.
+    ///
+    /// # Safety
+    ///
+    /// This method can only be executed by the [`super::RootCoordinator`]
+    /// after the collection phase of a simulation step.
+    unsafe fn notify(&mut self, t: T);
+}
+
+/// Output listener that calls a function for every message sent through an output port.
+pub(crate) struct OutputCallback<V, F> {
+    /// Output port of the model under simulation.
+    port: Arc<dyn Port>,
+    /// Function to be called with the time and the message.
+    f: F,
+    /// Type of the messages.
+    _value: std::marker::PhantomData<V>,
+}
+
+impl<V: DynRef + Clone, F> OutputCallback<V, F> {
+    /// Creates a new callback. The port must be of type `V`.
+    pub(crate) fn new(port: Arc<dyn Port>, f: F) -> Self {
+        debug_assert!(port.is_type::<V>());
+        Self {
+            port,
+            f,
+            _value: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: Time, V: DynRef + Clone, F: FnMut(T, &V) + 'static> OutputListener<T>
+    for OutputCallback<V, F>
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    unsafe fn notify(&mut self, t: T) {
+        for value in self.port.get_values::<V>().unwrap() {
+            (self.f)(t, value);
+        }
+    }
+}
+
+/// Output listener that stores all the messages sent through an output port.
+pub(crate) struct OutputHistory<T, V> {
+    /// Output port of the model under simulation.
+    port: Arc<dyn Port>,
+    /// Messages sent through the port, together with the time when they were sent.
+    pub(crate) values: Vec<(T, V)>,
+}
+
+impl<T: Time, V: DynRef + Clone> OutputHistory<T, V> {
+    /// Creates a new history. The port must be of type `V`.
+    pub(crate) fn new(port: Arc<dyn Port>) -> Self {
+        debug_assert!(port.is_type::<V>());
+        Self {
+            port,
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<T: Time, V: DynRef + Clone> OutputListener<T> for OutputHistory<T, V> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    unsafe fn notify(&mut self, t: T) {
+        let values = self.port.get_values::<V>().unwrap();
+        self.values
+            .extend(values.iter().map(|value| (t, value.clone())));
+    }
+}
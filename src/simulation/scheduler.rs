@@ -0,0 +1,183 @@
+use crate::modeling::Time;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Entry of the scheduler. It relates a subcomponent to its next simulation time.
+#[derive(Debug, Clone, Copy)]
+struct Entry<T> {
+    /// Time for the next state transition of the subcomponent.
+    t_next: T,
+    /// Index of the subcomponent in the coupled model.
+    index: usize,
+}
+
+impl<T: Time> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Time> Eq for Entry<T> {}
+
+impl<T: Time> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Time> Ord for Entry<T> {
+    /// Entries are sorted in reverse order, so the binary heap behaves as a min-heap.
+    /// Ties are broken by the index of the subcomponent.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .t_next
+            .total_cmp(&self.t_next)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Event scheduler of a coupled model. It keeps track of the next simulation time of every
+/// subcomponent, as well as the subcomponents that must be visited in the current simulation step.
+///
+/// The priority queue is lazy: when a subcomponent is rescheduled, its previous entry is not
+/// removed. Instead, outdated entries are discarded when they reach the top of the queue.
+#[derive(Debug)]
+pub(crate) struct Scheduler<T> {
+    /// Priority queue with the next simulation time of the subcomponents.
+    heap: BinaryHeap<Entry<T>>,
+    /// Indices of the subcomponents that must be visited in the current simulation step.
+    active: Vec<usize>,
+    /// Flags indicating whether a subcomponent is already in `active` or not.
+    is_active: Vec<bool>,
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            active: Vec::new(),
+            is_active: Vec::new(),
+        }
+    }
+}
+
+impl<T: Time> Scheduler<T> {
+    /// Removes all the entries of the scheduler and prepares it for `n` subcomponents.
+    pub(crate) fn reset(&mut self, n: usize) {
+        self.heap.clear();
+        self.active.clear();
+        self.is_active.clear();
+        self.is_active.resize(n, false);
+    }
+
+    /// Returns `true` if the scheduler contains too many outdated entries.
+    #[inline]
+    pub(crate) fn is_bloated(&self) -> bool {
+        self.heap.len() > 2 * self.is_active.len() + 16
+    }
+
+    /// Schedules a subcomponent for a given time. Passive subcomponents are not scheduled.
+    #[inline]
+    pub(crate) fn schedule(&mut self, index: usize, t_next: T) {
+        if t_next < T::INFINITY {
+            self.heap.push(Entry { t_next, index });
+        }
+    }
+
+    /// Returns the minimum next simulation time of the subcomponents.
+    /// `t_next` must return the current next simulation time of a subcomponent.
+    /// It is used for discarding outdated entries.
+    pub(crate) fn next_time(&mut self, t_next: impl Fn(usize) -> T) -> T {
+        while let Some(entry) = self.heap.peek() {
+            if entry.t_next == t_next(entry.index) {
+                return entry.t_next;
+            }
+            self.heap.pop();
+        }
+        T::INFINITY
+    }
+
+    /// Moves all the subcomponents with a next simulation time less than or equal to `t`
+    /// to the list of active subcomponents. `t_next` must return the current next simulation
+    /// time of a subcomponent. It is used for discarding outdated entries.
+    pub(crate) fn activate_imminent(&mut self, t: T, t_next: impl Fn(usize) -> T) {
+        while let Some(entry) = self.heap.peek() {
+            if entry.t_next > t {
+                break;
+            }
+            let entry = self.heap.pop().unwrap();
+            if entry.t_next == t_next(entry.index) {
+                self.activate(entry.index);
+            }
+        }
+    }
+
+    /// Adds a subcomponent to the list of active subcomponents (if it was not already there).
+    #[inline]
+    pub(crate) fn activate(&mut self, index: usize) {
+        if !self.is_active[index] {
+            self.is_active[index] = true;
+            self.active.push(index);
+        }
+    }
+
+    /// Returns the indices of the active subcomponents.
+    #[inline]
+    pub(crate) fn active(&self) -> &[usize] {
+        &self.active
+    }
+
+    /// Returns a flag for each subcomponent indicating whether it is active or not.
+    #[cfg(any(feature = "par_collection", feature = "par_transition"))]
+    #[inline]
+    pub(crate) fn active_flags(&self) -> &[bool] {
+        &self.is_active
+    }
+
+    /// Empties the list of active subcomponents.
+    #[inline]
+    pub(crate) fn clear_active(&mut self) {
+        for &index in self.active.iter() {
+            self.is_active[index] = false;
+        }
+        self.active.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheduler() {
+        let mut t_nexts = vec![3., 1., f64::INFINITY, 1.];
+        let mut scheduler = Scheduler::default();
+        scheduler.reset(t_nexts.len());
+        for (i, &t) in t_nexts.iter().enumerate() {
+            scheduler.schedule(i, t);
+        }
+        assert_eq!(1., scheduler.next_time(|i| t_nexts[i]));
+
+        scheduler.activate_imminent(1., |i| t_nexts[i]);
+        assert_eq!(&[1, 3], scheduler.active());
+        scheduler.activate(3);
+        scheduler.activate(2);
+        assert_eq!(&[1, 3, 2], scheduler.active());
+
+        // component 0 is rescheduled, so its previous entry becomes outdated
+        t_nexts = vec![5., 2., 4., f64::INFINITY];
+        let active = scheduler.active().to_vec();
+        scheduler.clear_active();
+        for i in active {
+            scheduler.schedule(i, t_nexts[i]);
+        }
+        scheduler.schedule(0, t_nexts[0]);
+        assert!(scheduler.active().is_empty());
+        assert_eq!(2., scheduler.next_time(|i| t_nexts[i]));
+
+        scheduler.activate_imminent(4., |i| t_nexts[i]);
+        assert_eq!(&[1, 2], scheduler.active());
+        scheduler.clear_active();
+        assert_eq!(5., scheduler.next_time(|i| t_nexts[i]));
+    }
+}
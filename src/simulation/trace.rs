@@ -0,0 +1,379 @@
+use super::SimObserver;
+use crate::modeling::Time;
+use crate::DynRef;
+use std::fmt::{Debug, Write as _};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// Output format of a [`TraceWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Comma-separated values with a header line.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+/// Mutable state of a [`TraceWriter`].
+#[derive(Debug)]
+struct State<W> {
+    /// Destination of the trace.
+    writer: W,
+    /// First error found when writing the trace. Once an error occurs, the trace is not written anymore.
+    error: Option<io::Error>,
+    /// Reusable buffer for formatting records.
+    buf: String,
+}
+
+/// Simulation observer that writes a structured trace of the simulation.
+///
+/// Message values are only recorded for traced ports, which are created with
+/// [`Component::add_traced_in_port`](crate::modeling::Component::add_traced_in_port) and
+/// [`Component::add_traced_out_port`](crate::modeling::Component::add_traced_out_port).
+/// Messages of ports created with [`Component::add_in_port`](crate::modeling::Component::add_in_port)
+/// or [`Component::add_out_port`](crate::modeling::Component::add_out_port) are recorded as the
+/// name of their type between angle brackets (e.g., `<u32>`).
+///
+/// Every state transition and every message sent or received by an atomic model is written
+/// as a record with the following fields:
+///
+/// - `t`: simulation time of the event.
+/// - `path`: full path of the model (e.g., `root.subcoupled.atomic`).
+/// - `event`: kind of event (`start`, `lambda`, `output`, `input`, `delta_int`, `delta_ext`, `delta_conf`, or `stop`).
+/// - `port`: name of the port (only for `output` and `input` events).
+/// - `value`: [`Debug`] representation of the message (only for `output` and `input` events).
+///   Messages of ports that are not traced are represented by the name of their type.
+///
+/// Traces can be written in CSV ([`TraceWriter::csv`]) or JSON Lines ([`TraceWriter::jsonl`]) format.
+/// I/O errors do not interrupt the simulation. Instead, the first error is returned by
+/// [`TraceWriter::flush`] or [`TraceWriter::into_inner`].
+#[derive(Debug)]
+pub struct TraceWriter<W: Write> {
+    format: Format,
+    state: Mutex<State<W>>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Creates a new trace writer with the given format.
+    fn new(format: Format, writer: W) -> Self {
+        let state = State {
+            writer,
+            error: None,
+            buf: String::new(),
+        };
+        Self {
+            format,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Creates a new trace writer in CSV format. It writes the header line immediately.
+    pub fn csv(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "t,path,event,port,value")?;
+        Ok(Self::new(Format::Csv, writer))
+    }
+
+    /// Creates a new trace writer in JSON Lines format.
+    pub fn jsonl(writer: W) -> Self {
+        Self::new(Format::JsonLines, writer)
+    }
+
+    /// Flushes the underlying writer. It returns the first error found when writing the trace (if any).
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(error) = state.error.take() {
+            return Err(error);
+        }
+        state.writer.flush()
+    }
+
+    /// Flushes and returns the underlying writer.
+    /// It returns the first error found when writing the trace (if any).
+    pub fn into_inner(self) -> io::Result<W> {
+        let state = self.state.into_inner().unwrap();
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+        let mut writer = state.writer;
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    /// Writes a new record to the trace.
+    fn record<T: Time>(&self, t: T, path: &str, event: &str, message: Option<(&str, &dyn Debug)>) {
+        let mut state = self.state.lock().unwrap();
+        if state.error.is_some() {
+            return;
+        }
+        let State { writer, error, buf } = &mut *state;
+        buf.clear();
+        let is_infinite = t == T::INFINITY;
+        match self.format {
+            Format::Csv => {
+                match is_infinite {
+                    true => buf.push_str("inf,"),
+                    false => write!(buf, "{t},").unwrap(),
+                }
+                csv_field(buf, path);
+                write!(buf, ",{event},").unwrap();
+                if let Some((port, value)) = message {
+                    csv_field(buf, port);
+                    buf.push(',');
+                    csv_field(buf, &format!("{value:?}"));
+                } else {
+                    buf.push(',');
+                }
+            }
+            Format::JsonLines => {
+                match is_infinite {
+                    true => buf.push_str("{\"t\":\"inf\",\"path\":"),
+                    false => write!(buf, "{{\"t\":{t},\"path\":").unwrap(),
+                }
+                json_string(buf, path);
+                write!(buf, ",\"event\":\"{event}\"").unwrap();
+                if let Some((port, value)) = message {
+                    buf.push_str(",\"port\":");
+                    json_string(buf, port);
+                    buf.push_str(",\"value\":");
+                    json_string(buf, &format!("{value:?}"));
+                }
+                buf.push('}');
+            }
+        }
+        buf.push('\n');
+        if let Err(e) = writer.write_all(buf.as_bytes()) {
+            *error = Some(e);
+        }
+    }
+}
+
+/// Appends a CSV field to a buffer. Fields with commas, quotes, or line breaks are quoted.
+fn csv_field(buf: &mut String, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        buf.push('"');
+        buf.push_str(&field.replace('"', "\"\""));
+        buf.push('"');
+    } else {
+        buf.push_str(field);
+    }
+}
+
+/// Appends a JSON string to a buffer, escaping special characters.
+fn json_string(buf: &mut String, s: &str) {
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if c.is_control() => write!(buf, "\\u{:04x}", c as u32).unwrap(),
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+impl<T: Time, W: Write + DynRef> SimObserver<T> for TraceWriter<W> {
+    fn on_start(&self, path: &str, t: T) {
+        self.record(t, path, "start", None);
+    }
+
+    fn on_lambda(&self, path: &str, t: T) {
+        self.record(t, path, "lambda", None);
+    }
+
+    fn on_output(&self, path: &str, t: T, port: &str, value: &dyn Debug) {
+        self.record(t, path, "output", Some((port, value)));
+    }
+
+    fn on_input(&self, path: &str, t: T, port: &str, value: &dyn Debug) {
+        self.record(t, path, "input", Some((port, value)));
+    }
+
+    fn on_delta_int(&self, path: &str, t: T) {
+        self.record(t, path, "delta_int", None);
+    }
+
+    fn on_delta_ext(&self, path: &str, t: T, _e: T) {
+        self.record(t, path, "delta_ext", None);
+    }
+
+    fn on_delta_conf(&self, path: &str, t: T) {
+        self.record(t, path, "delta_conf", None);
+    }
+
+    fn on_stop(&self, path: &str, t: T) {
+        self.record(t, path, "stop", None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modeling::{
+        Atomic, Component, Coupled, HasComponent, InPort, InputCtx, OutPort, OutputCtx,
+    };
+    use crate::simulation::RootCoordinator;
+    use std::sync::Arc;
+
+    /// Atomic model that periodically sends the number of messages it has sent so far.
+    /// It becomes passive after receiving a message. Its ports may be traced or not.
+    struct Generator {
+        component: Component,
+        period: f64,
+        sigma: f64,
+        count: usize,
+        input: InPort<bool>,
+        output: OutPort<usize>,
+    }
+
+    impl Generator {
+        fn new(name: &str, period: f64, traced: bool) -> Self {
+            let mut component = Component::new(name);
+            let (input, output) = match traced {
+                true => (
+                    component.add_traced_in_port("input"),
+                    component.add_traced_out_port("output"),
+                ),
+                false => (
+                    component.add_in_port("input"),
+                    component.add_out_port("output"),
+                ),
+            };
+            Self {
+                component,
+                period,
+                sigma: period,
+                count: 0,
+                input,
+                output,
+            }
+        }
+    }
+
+    impl HasComponent for Generator {
+        type Time = f64;
+
+        fn get_component(&self) -> &Component {
+            &self.component
+        }
+
+        fn get_component_mut(&mut self) -> &mut Component {
+            &mut self.component
+        }
+    }
+
+    impl Atomic for Generator {
+        fn lambda(&self, output: &OutputCtx) {
+            output.add_value(&self.output, self.count);
+        }
+
+        fn delta_int(&mut self) {
+            self.count += 1;
+            self.sigma = self.period;
+        }
+
+        fn delta_ext(&mut self, e: f64, input: &InputCtx) {
+            self.sigma -= e;
+            if !input.is_empty(&self.input) {
+                self.sigma = f64::INFINITY;
+            }
+        }
+
+        fn ta(&self) -> f64 {
+            self.sigma
+        }
+    }
+
+    /// Returns a coupled model with a generator coupled to the input and output ports of the model.
+    fn create_model(traced: bool) -> Coupled {
+        let mut coupled = Coupled::new("root");
+        coupled.add_in_port::<bool>("input");
+        coupled.add_out_port::<usize>("output");
+        coupled.add_component(Box::new(Generator::new("generator", 2., traced)));
+        coupled.add_eic("input", "generator", "input");
+        coupled.add_eoc("generator", "output", "output");
+        coupled
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        let trace = TraceWriter::csv(Vec::new()).unwrap();
+        trace.on_output("root.a", 1.5, "out", &"a,\"b\"");
+        trace.on_stop("root", f64::INFINITY);
+        let output = String::from_utf8(trace.into_inner().unwrap()).unwrap();
+        let expected = "t,path,event,port,value\n\
+                        1.5,root.a,output,out,\"\"\"a,\\\"\"b\\\"\"\"\"\"\n\
+                        inf,root,stop,,\n";
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn test_jsonl_escape() {
+        let trace = TraceWriter::jsonl(Vec::new());
+        trace.on_input("root.a", 2., "in", &"x\ny");
+        trace.on_stop("root", f64::INFINITY);
+        let output = String::from_utf8(trace.into_inner().unwrap()).unwrap();
+        let expected = "{\"t\":2,\"path\":\"root.a\",\"event\":\"input\",\"port\":\"in\",\"value\":\"\\\"x\\\\ny\\\"\"}\n\
+                        {\"t\":\"inf\",\"path\":\"root\",\"event\":\"stop\"}\n";
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn test_trace() {
+        let trace = Arc::new(TraceWriter::csv(Vec::new()).unwrap());
+        let mut simulator = RootCoordinator::new(create_model(true));
+        simulator.set_observer(trace.clone());
+        simulator.start();
+        simulator.step();
+        simulator.inject("input", &[true], 3.);
+        simulator.finish();
+        simulator.remove_observer();
+        let trace = Arc::try_unwrap(trace).unwrap().into_inner().unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        let expected = "t,path,event,port,value\n\
+                        0,root.generator,start,,\n\
+                        0,root,start,,\n\
+                        2,root.generator,lambda,,\n\
+                        2,root.generator,output,output,0\n\
+                        2,root.generator,delta_int,,\n\
+                        3,root.generator,input,input,true\n\
+                        3,root.generator,delta_ext,,\n\
+                        3,root.generator,stop,,\n\
+                        3,root,stop,,\n";
+        assert_eq!(expected, trace);
+
+        let trace = Arc::new(TraceWriter::jsonl(Vec::new()));
+        let mut simulator = RootCoordinator::new(create_model(true));
+        simulator.set_observer(trace.clone());
+        simulator.simulate(3.);
+        simulator.remove_observer();
+        let trace = Arc::try_unwrap(trace).unwrap().into_inner().unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        let output = "{\"t\":2,\"path\":\"root.generator\",\"event\":\"output\",\"port\":\"output\",\"value\":\"0\"}";
+        assert_eq!(Some(output), trace.lines().nth(3));
+    }
+
+    #[test]
+    fn test_trace_untraced() {
+        // Messages of ports that are not traced are represented by the name of their type
+        let trace = Arc::new(TraceWriter::csv(Vec::new()).unwrap());
+        let mut simulator = RootCoordinator::new(create_model(false));
+        simulator.set_observer(trace.clone());
+        simulator.start();
+        simulator.inject("input", &[true], 2.);
+        simulator.remove_observer();
+        let trace = Arc::try_unwrap(trace).unwrap().into_inner().unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        let expected = "t,path,event,port,value\n\
+                        0,root.generator,start,,\n\
+                        0,root,start,,\n\
+                        2,root.generator,lambda,,\n\
+                        2,root.generator,output,output,<usize>\n\
+                        2,root.generator,input,input,<bool>\n\
+                        2,root.generator,delta_conf,,\n";
+        assert_eq!(expected, trace);
+    }
+}
@@ -0,0 +1,249 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::collections::HashSet;
+use syn::parse::ParseStream;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Field, Fields, GenericArgument, Ident,
+    LitStr, Meta, PathArguments, Result, Token, Type,
+};
+
+/// Derives the `xdevs::modeling::HasComponent` trait for an atomic model,
+/// and a `new_with_ports` constructor that creates its component and ports.
+///
+/// The component of the model is the field marked with `#[component]`,
+/// or the field named `component` if no field is marked.
+/// Fields marked with `#[in_port]` or `#[out_port]` are input or output ports of the model.
+/// By default, ports are named after their field. A different name can be set with
+/// `#[in_port("name")]` or `#[out_port("name")]`. Traced ports, whose messages are shown by
+/// simulation observers, are marked with `#[in_port(traced)]` or `#[out_port("name", traced)]`.
+/// The remaining fields are the state of the model, and the constructor receives them
+/// in declaration order after the name of the model:
+///
+/// ```ignore
+/// #[derive(Atomic)]
+/// struct Generator {
+///     component: Component,
+///     #[in_port("stop")]
+///     input: InPort<bool>,
+///     #[out_port(traced)]
+///     output: OutPort<usize>,
+///     period: f64,
+/// }
+///
+/// let generator = Generator::new_with_ports("generator", 1.);
+/// ```
+#[proc_macro_derive(Atomic, attributes(component, in_port, out_port))]
+pub fn derive_atomic(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_atomic(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Settings of a port of an atomic model.
+struct PortAttr {
+    /// Name of the port.
+    name: LitStr,
+    /// Whether the messages of the port are shown by simulation observers.
+    traced: bool,
+}
+
+/// Role of a field of an atomic model.
+enum FieldKind {
+    Component,
+    InPort(PortAttr),
+    OutPort(PortAttr),
+    State,
+}
+
+/// Returns the role of a field of an atomic model.
+fn field_kind(field: &Field) -> Result<FieldKind> {
+    let ident = field.ident.as_ref().unwrap();
+    let mut kind = None;
+    for attr in &field.attrs {
+        let new_kind = if attr.path().is_ident("component") {
+            attr.meta.require_path_only()?;
+            FieldKind::Component
+        } else if attr.path().is_ident("in_port") {
+            FieldKind::InPort(port_attr(ident, attr)?)
+        } else if attr.path().is_ident("out_port") {
+            FieldKind::OutPort(port_attr(ident, attr)?)
+        } else {
+            continue;
+        };
+        if kind.replace(new_kind).is_some() {
+            return Err(Error::new_spanned(attr, "field has more than one role"));
+        }
+    }
+    Ok(kind.unwrap_or(FieldKind::State))
+}
+
+/// Returns the settings of a port. By default, it is named after its field and it is not traced.
+fn port_attr(ident: &Ident, attr: &Attribute) -> Result<PortAttr> {
+    let name = ident.to_string();
+    let mut port = PortAttr {
+        name: LitStr::new(name.trim_start_matches("r#"), ident.span()),
+        traced: false,
+    };
+    if let Meta::Path(_) = &attr.meta {
+        return Ok(port);
+    }
+    attr.parse_args_with(|input: ParseStream| {
+        if input.peek(LitStr) {
+            port.name = input.parse()?;
+            if input.is_empty() {
+                return Ok(());
+            }
+            input.parse::<Token![,]>()?;
+        }
+        let flag: Ident = input.parse()?;
+        if flag != "traced" {
+            return Err(Error::new_spanned(flag, "expected a port name or `traced`"));
+        }
+        port.traced = true;
+        Ok(())
+    })?;
+    Ok(port)
+}
+
+/// Returns the time type of a component (e.g., `u64` for `Component<u64>`).
+/// By default, it is `f64`.
+fn time_type(ty: &Type) -> TokenStream2 {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(GenericArgument::Type(time)) = args.args.first() {
+                    return quote!(#time);
+                }
+            }
+        }
+    }
+    quote!(f64)
+}
+
+/// Returns the `cfg` attributes of a field, so they can be forwarded to the generated code.
+fn cfg_attrs(field: &Field) -> Vec<&Attribute> {
+    field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect()
+}
+
+fn expand_atomic(input: DeriveInput) -> Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "Atomic can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "Atomic can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut component = None;
+    let mut in_ports = Vec::new();
+    let mut out_ports = Vec::new();
+    let mut state = Vec::new();
+    for field in fields.named.iter() {
+        match field_kind(field)? {
+            FieldKind::Component if component.is_some() => {
+                return Err(Error::new_spanned(field, "duplicate component field"));
+            }
+            FieldKind::Component => component = Some(field),
+            FieldKind::InPort(name) => in_ports.push((field, name)),
+            FieldKind::OutPort(name) => out_ports.push((field, name)),
+            FieldKind::State => state.push(field),
+        }
+    }
+    // If there is no field marked as component, we look for a field named `component`
+    if component.is_none() {
+        if let Some(i) = state
+            .iter()
+            .position(|f| f.ident.as_ref().unwrap() == "component")
+        {
+            component = Some(state.remove(i));
+        }
+    }
+    let Some(component) = component else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "atomic models require a field marked with #[component] or named `component`",
+        ));
+    };
+    for ports in [&in_ports, &out_ports] {
+        let mut names = HashSet::new();
+        for (_, port) in ports {
+            if !names.insert(port.name.value()) {
+                return Err(Error::new_spanned(&port.name, "duplicate port name"));
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let component_ident = component.ident.as_ref().unwrap();
+    let time = time_type(&component.ty);
+
+    let in_idents = in_ports.iter().map(|(f, _)| f.ident.as_ref().unwrap());
+    let in_names = in_ports.iter().map(|(_, port)| &port.name);
+    let in_ctors = in_ports.iter().map(|(_, port)| match port.traced {
+        true => quote!(add_traced_in_port),
+        false => quote!(add_in_port),
+    });
+    let out_idents = out_ports.iter().map(|(f, _)| f.ident.as_ref().unwrap());
+    let out_names = out_ports.iter().map(|(_, port)| &port.name);
+    let out_ctors = out_ports.iter().map(|(_, port)| match port.traced {
+        true => quote!(add_traced_out_port),
+        false => quote!(add_out_port),
+    });
+    let port_idents = in_ports
+        .iter()
+        .chain(&out_ports)
+        .map(|(f, _)| f.ident.as_ref().unwrap());
+    let params = state.iter().map(|f| {
+        let (attrs, ident, ty) = (cfg_attrs(f), f.ident.as_ref().unwrap(), &f.ty);
+        quote!(#(#attrs)* #ident: #ty)
+    });
+    let state_fields = state.iter().map(|f| {
+        let (attrs, ident) = (cfg_attrs(f), f.ident.as_ref().unwrap());
+        quote!(#(#attrs)* #ident)
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::xdevs::modeling::HasComponent for #ident #ty_generics #where_clause {
+            type Time = #time;
+
+            #[inline]
+            fn get_component(&self) -> &::xdevs::modeling::Component<Self::Time> {
+                &self.#component_ident
+            }
+
+            #[inline]
+            fn get_component_mut(&mut self) -> &mut ::xdevs::modeling::Component<Self::Time> {
+                &mut self.#component_ident
+            }
+        }
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Creates a new atomic model with the given name and initial state.
+            /// It also creates the component of the model and its ports.
+            #[allow(dead_code, clippy::too_many_arguments)]
+            #vis fn new_with_ports(name: &str, #(#params),*) -> Self {
+                let mut #component_ident = ::xdevs::modeling::Component::new(name);
+                #(let #in_idents = #component_ident.#in_ctors(#in_names);)*
+                #(let #out_idents = #component_ident.#out_ctors(#out_names);)*
+                Self {
+                    #component_ident,
+                    #(#port_idents,)*
+                    #(#state_fields,)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,11 @@
+use xdevs::modeling::{Atomic, Component};
+
+#[derive(Atomic)]
+struct Generator {
+    #[component]
+    first: Component,
+    #[component]
+    second: Component,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use xdevs::modeling::{Atomic, Component, InPort};
+
+#[derive(Atomic)]
+struct Processor {
+    component: Component,
+    #[in_port("input")]
+    jobs: InPort<usize>,
+    #[in_port]
+    input: InPort<bool>,
+}
+
+fn main() {}
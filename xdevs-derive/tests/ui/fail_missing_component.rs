@@ -0,0 +1,10 @@
+use xdevs::modeling::{Atomic, Component, OutPort};
+
+#[derive(Atomic)]
+struct Generator {
+    inner: Component,
+    #[out_port]
+    output: OutPort<usize>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use xdevs::modeling::Atomic;
+
+#[derive(Atomic)]
+enum Generator {
+    Active,
+    Passive,
+}
+
+#[derive(Atomic)]
+struct Processor(usize);
+
+fn main() {}
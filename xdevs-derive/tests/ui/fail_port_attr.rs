@@ -0,0 +1,18 @@
+use xdevs::modeling::{Atomic, Component, InPort, OutPort};
+
+#[derive(Atomic)]
+struct Relay {
+    component: Component,
+    #[in_port(hidden)]
+    input: InPort<usize>,
+}
+
+#[derive(Atomic)]
+struct Processor {
+    component: Component,
+    #[in_port]
+    #[out_port]
+    port: OutPort<usize>,
+}
+
+fn main() {}
@@ -0,0 +1,73 @@
+use xdevs::modeling::{
+    Atomic, Component, Coupled, HasComponent, InPort, InputCtx, OutPort, OutputCtx,
+};
+use xdevs::simulation::RootCoordinator;
+
+#[derive(Atomic)]
+struct Generator {
+    component: Component,
+    #[in_port("stop")]
+    input: InPort<bool>,
+    #[out_port(traced)]
+    output: OutPort<usize>,
+    period: f64,
+    count: usize,
+}
+
+impl Atomic for Generator {
+    fn lambda(&self, output: &OutputCtx) {
+        output.add_value(&self.output, self.count);
+    }
+
+    fn delta_int(&mut self) {
+        self.count += 1;
+    }
+
+    fn delta_ext(&mut self, _e: f64, input: &InputCtx) {
+        if !input.is_empty(&self.input) {
+            self.period = f64::INFINITY;
+        }
+    }
+
+    fn ta(&self) -> f64 {
+        self.period
+    }
+}
+
+// Input and output ports have separate namespaces, and components may use other time types
+#[derive(Atomic)]
+struct Ticker {
+    #[component]
+    ticker: Component<u64>,
+    #[in_port("tick")]
+    input: InPort<u64>,
+    #[out_port("tick", traced)]
+    output: OutPort<u64>,
+}
+
+fn main() {
+    let generator = Generator::new_with_ports("generator", 1., 0);
+    assert_eq!("generator", generator.get_component().get_name());
+    assert_eq!(1., generator.ta());
+
+    // Ports are named after their attributes or their fields
+    let mut coupled = Coupled::new("root");
+    coupled.add_in_port::<bool>("stop");
+    coupled.add_out_port::<usize>("output");
+    coupled.add_component(Box::new(generator));
+    coupled.add_eic("stop", "generator", "stop");
+    coupled.add_eoc("generator", "output", "output");
+    let mut simulator = RootCoordinator::new(coupled);
+    simulator.collect_output::<usize>("output");
+    simulator.start();
+    simulator.inject("stop", &[true], 2.5);
+    simulator.simulate_until(5.);
+    let expected = [(1., 0), (2., 1)];
+    assert_eq!(&expected, simulator.get_output::<usize>("output").unwrap());
+
+    let mut ticker = Ticker::new_with_ports("ticker");
+    let component: &mut Component<u64> = ticker.get_component_mut();
+    assert!(component.try_add_in_port::<u64>("tick").is_err());
+    assert!(component.try_add_out_port::<u64>("tick").is_err());
+    assert!(component.try_add_in_port::<u64>("input").is_ok());
+}